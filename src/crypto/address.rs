@@ -0,0 +1,132 @@
+use std::fmt;
+use std::str::FromStr;
+use sha2::{Digest, Sha256};
+use crate::crypto::hashing::{Hash, HASH_SIZE};
+use crate::models::chain_params::Network;
+use crate::models::errors::AddressError;
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const CHECKSUM_SIZE: usize = 4;
+
+/// Endereço de recebimento: hash da chave pública + byte de versão da rede
+///
+/// Codificado em Base58Check (versão, hash e 4 bytes de checksum), então um
+/// erro de digitação é detectado antes de enviar moedas. A chave pública só
+/// aparece na corrente quando o output é gasto
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Address {
+    pub version: u8,                    // Ver `Network::address_version`
+    pub pubkey_hash: Hash,              // SHA-256 da chave pública
+}
+
+impl Address {
+    pub fn new(version: u8, pubkey_hash: Hash) -> Self {
+        Address { version, pubkey_hash }
+    }
+
+    /// Endereço da chave pública (bytes crus) na rede `network`
+    pub fn from_pubkey(pubkey: &[u8], network: Network) -> Self {
+        Address::new(network.address_version(), Hash::sha256(pubkey))
+    }
+
+    /// Decodifica e exige que o endereço seja da rede `network`
+    pub fn parse(text: &str, network: Network) -> Result<Self, AddressError> {
+        let address: Address = text.parse()?;
+        let expected = network.address_version();
+        if address.version != expected {
+            return Err(AddressError::WrongNetwork { expected, found: address.version });
+        }
+        Ok(address)
+    }
+
+    fn payload(&self) -> Vec<u8> {
+        let mut payload = vec![self.version];
+        payload.extend_from_slice(self.pubkey_hash.as_bytes());
+        payload
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = self.payload();
+        bytes.extend_from_slice(&checksum(&bytes));
+        write!(f, "{}", base58_encode(&bytes))
+    }
+}
+
+impl FromStr for Address {
+    type Err = AddressError;
+
+    /// Decodifica qualquer rede, validando alfabeto, tamanho e checksum
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let bytes = base58_decode(text)?;
+        if bytes.len() != 1 + HASH_SIZE + CHECKSUM_SIZE {
+            return Err(AddressError::InvalidLength { length: bytes.len() });
+        }
+        let (payload, check) = bytes.split_at(1 + HASH_SIZE);
+        if checksum(payload) != check {
+            return Err(AddressError::InvalidChecksum);
+        }
+
+        let mut pubkey_hash = [0u8; HASH_SIZE];
+        pubkey_hash.copy_from_slice(&payload[1..]);
+        Ok(Address::new(payload[0], Hash::from_bytes(pubkey_hash)))
+    }
+}
+
+/// Primeiros bytes do SHA-256 duplo
+fn checksum(payload: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    let digest = Sha256::digest(Sha256::digest(payload));
+    let mut check = [0u8; CHECKSUM_SIZE];
+    check.copy_from_slice(&digest[..CHECKSUM_SIZE]);
+    check
+}
+
+/// Cada byte zero à esquerda vira um '1'; o resto é o número em base 58
+fn base58_encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+
+    // Dígitos em base 58, do menos significativo para o mais
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in bytes {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut text = "1".repeat(zeros);
+    text.extend(digits.iter().rev().map(|&digit| BASE58_ALPHABET[digit as usize] as char));
+    text
+}
+
+fn base58_decode(text: &str) -> Result<Vec<u8>, AddressError> {
+    // Bytes do número, do menos significativo para o mais
+    let mut bytes: Vec<u8> = Vec::new();
+    for (position, character) in text.chars().enumerate() {
+        let value = BASE58_ALPHABET.iter()
+            .position(|&symbol| symbol as char == character)
+            .ok_or(AddressError::InvalidCharacter { character, position })?;
+        let mut carry = value as u32;
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let zeros = text.chars().take_while(|&character| character == '1').count();
+    bytes.extend(std::iter::repeat_n(0, zeros));
+    bytes.reverse();
+    Ok(bytes)
+}
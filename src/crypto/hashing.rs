@@ -6,7 +6,7 @@ use std::collections::HashMap;
 use crate::models::transaction::Transaction;
 use serde::{Serialize, Deserialize, Serializer, Deserializer};
 
-const HASH_SIZE: usize = 32;
+pub const HASH_SIZE: usize = 32;
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -45,7 +45,27 @@ impl Hash {
     pub fn as_bytes(&self) -> &[u8] {
         &self.0
     }
+
+    pub fn from_bytes(bytes: [u8; HASH_SIZE]) -> Self {
+        Hash(bytes)
+    }
+
+    /// Inverso de `to_hex`; `None` se não forem 32 bytes em hex
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let bytes = hex::decode(hex).ok()?;
+        Some(Hash(bytes.try_into().ok()?))
+    }
     
+    /// SHA-256 simples dos bytes crus, sem serialização
+    ///
+    /// É o hash que o opcode `Sha256` calcula, usado para travar um output
+    /// na revelação de um segredo (HTLC)
+    pub fn sha256(data: &[u8]) -> Self {
+        let mut hash = [0u8; HASH_SIZE];
+        hash.copy_from_slice(&Sha256::digest(data));
+        Hash(hash)
+    }
+
     /// Gera o dado de qualquer hash serializável
     pub fn hash_data<T: Serialize>(data: &T) -> Self {
         let bytes = bincode::serialize(data).expect("Falha na serializacao bincode");
@@ -55,7 +75,7 @@ impl Hash {
         let first_hash = hasher.finalize();
 
         let mut hasher2 = Sha256::new();
-        hasher2.update(&first_hash);
+        hasher2.update(first_hash);
         let final_hash = hasher2.finalize();
 
         let mut hash = [0u8; HASH_SIZE];
@@ -173,7 +193,7 @@ impl Hash {
         // Gerar os hashes iniciais (folhas da árvore)
         let mut current_level: Vec<Hash> = transactions
             .iter()
-            .map(|tx| Hash::hash_data(tx))
+            .map(Hash::hash_data)
             .collect();
 
         // Subir a árvore até sobrar apenas o hash
@@ -208,7 +228,7 @@ impl Hash {
 
         // Segundo hash
         let mut hasher2 = Sha256::new();
-        hasher2.update(&first);
+        hasher2.update(first);
         let result = hasher2.finalize();
 
         let mut hash_arr = [0u8; HASH_SIZE];
@@ -237,7 +257,7 @@ impl Hash {
         let mut siblings = Vec::new();
         let mut current_level: Vec<Hash> = transactions
             .iter()
-            .map(|tx| Hash::hash_data(tx))
+            .map(Hash::hash_data)
             .collect();
         
         let mut current_index = tx_index;
@@ -252,7 +272,7 @@ impl Hash {
 
                 // Se o índice atual está neste chunk, salva o irmão
                 if i == current_index / 2 {
-                    if current_index % 2 == 0 {
+                    if current_index.is_multiple_of(2) {
                         // TX está à esquerda, salva o da direita
                         siblings.push(*right);
                     } else {
@@ -291,7 +311,7 @@ impl Hash {
 
         // Reconstrói o caminho até a raiz
         for sibling in &proof.siblings {
-            current_hash = if index % 2 == 0 {
+            current_hash = if index.is_multiple_of(2) {
                 // TX está à esquerda
                 Hash::calculate_hash_tree_branch(&current_hash, sibling)
             } else {
@@ -1,2 +1,3 @@
+pub mod address;
 pub mod hashing;
 pub mod wallet;
\ No newline at end of file
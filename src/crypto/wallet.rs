@@ -1,6 +1,11 @@
-use ed25519_dalek::{VerifyingKey, SigningKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use rand::rngs::OsRng;
 use sha2::{Digest, Sha256};
+use crate::crypto::address::Address;
+use crate::crypto::hashing::Hash;
+use crate::models::chain_params::Network;
+use crate::models::script::{Htlc, Op, Script};
+use crate::models::transaction::{Input, Output, Transaction, SEQUENCE_FINAL};
 
 #[derive(Debug)]
 pub struct Wallet {
@@ -9,6 +14,8 @@ pub struct Wallet {
 }
 
 impl Wallet {
+    /// Carteira com chave aleatória; sem `Default`, já que cada chamada gera outra chave
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         let mut csprng = OsRng;
         let secret: SigningKey = SigningKey::generate(&mut csprng);
@@ -16,9 +23,13 @@ impl Wallet {
         Wallet { secret, public }
     }
 
-    /// Retorna a chave pública formatada como string hex (para o endereço)
-    pub fn address(&self) -> String {
-        hex::encode(self.public.as_bytes())
+    /// Endereço de recebimento desta carteira na rede `network`
+    pub fn address(&self, network: Network) -> Address {
+        Address::from_pubkey(self.public.as_bytes(), network)
+    }
+
+    pub fn pubkey_hash(&self) -> Hash {
+        Hash::sha256(self.public.as_bytes())
     }
 
     pub fn from_seed(seed_text: &str) -> Self {
@@ -30,4 +41,112 @@ impl Wallet {
         let public = VerifyingKey::from(&secret);
         Wallet { secret, public }
     }
-}
\ No newline at end of file
+
+    /// Co-assina o input `input_index`, que gasta o output multisig `spent`
+    ///
+    /// A assinatura entra na posição da chave desta carteira, mantendo as já
+    /// coletadas pelos outros donos. Retorna quantas assinaturas o input tem
+    /// agora, ou `None` se o output não é multisig ou a chave não faz parte dele
+    pub fn cosign_multisig(&self, tx: &mut Transaction, input_index: usize, spent: &Output) -> Option<usize> {
+        let (threshold, pubkeys) = spent.script_pubkey.as_multisig()?;
+        let my_index = pubkeys.iter().position(|key| key.as_slice() == self.public.as_bytes())?;
+        let data = tx.get_data_to_sign(input_index, spent)?;
+
+        // Descobre de qual chave é cada assinatura já presente
+        let mut by_key: Vec<Option<Vec<u8>>> = vec![None; pubkeys.len()];
+        for op in &tx.inputs[input_index].script_sig.0 {
+            let Op::PushData(signature) = op else { continue; };
+            if let Some(index) = pubkeys.iter().position(|key| signature_matches(key, signature, &data)) {
+                by_key[index] = Some(signature.clone());
+            }
+        }
+        by_key[my_index] = Some(self.secret.sign(&data).to_vec());
+
+        // CHECKMULTISIG consome exatamente M assinaturas, na ordem das chaves
+        let signatures: Vec<Op> = by_key.into_iter().flatten().take(threshold).map(Op::PushData).collect();
+        let count = signatures.len();
+        tx.inputs[input_index].script_sig = Script::new(signatures);
+        Some(count)
+    }
+
+    /// Termos de um HTLC financiado por esta carteira (que fica com o reembolso)
+    pub fn htlc_terms(&self, payee: &VerifyingKey, hash_lock: Hash, timeout: u64) -> Htlc {
+        Htlc { payee: payee.to_bytes(), hash_lock, refund: self.public.to_bytes(), timeout }
+    }
+
+    /// Trava `amount` de um output pago ao endereço desta carteira num HTLC
+    ///
+    /// O que sobra depois de `amount` e `fee` volta para o mesmo endereço como troco.
+    /// Retorna `None` se o output não é desta carteira ou não cobre o valor
+    pub fn fund_htlc(&self, prev_tx_hash: Hash, output_index: usize, spent: &Output,
+                     htlc: &Htlc, amount: u64, fee: u64) -> Option<Transaction> {
+        if spent.pubkey_hash()? != self.pubkey_hash() {
+            return None;
+        }
+        let change = spent.value.checked_sub(amount)?.checked_sub(fee)?;
+
+        let mut outputs = vec![Output::htlc(amount, htlc)];
+        if change > 0 {
+            outputs.push(Output { value: change, script_pubkey: spent.script_pubkey.clone() });
+        }
+        let mut tx = Transaction::new(vec![Input::new(prev_tx_hash, output_index)], outputs);
+        tx.sign_input(0, spent, &self.secret).then_some(tx)
+    }
+
+    /// Resgata um HTLC revelando o segredo: `<assinatura> <segredo> 1`
+    ///
+    /// O valor (menos a taxa) vai para `to`. Retorna `None` se a carteira não
+    /// é o destinatário ou o segredo não bate com o hash
+    pub fn claim_htlc(&self, prev_tx_hash: Hash, output_index: usize, spent: &Output,
+                      preimage: &[u8], to: &Address, fee: u64) -> Option<Transaction> {
+        let htlc = spent.script_pubkey.as_htlc()?;
+        if htlc.payee != self.public.to_bytes() || Hash::sha256(preimage) != htlc.hash_lock {
+            return None;
+        }
+        let branch = vec![Op::PushData(preimage.to_vec()), Op::PushInt(1)];
+        self.spend_htlc(Input::new(prev_tx_hash, output_index), 0, spent, to, fee, branch)
+    }
+
+    /// Recupera um HTLC vencido pelo caminho do reembolso: `<assinatura> 0`
+    ///
+    /// A transação leva `lock_time = timeout`, então só entra em blocos acima
+    /// dessa altura. Retorna `None` se a carteira não é a dona do reembolso
+    pub fn refund_htlc(&self, prev_tx_hash: Hash, output_index: usize, spent: &Output,
+                       to: &Address, fee: u64) -> Option<Transaction> {
+        let htlc = spent.script_pubkey.as_htlc()?;
+        if htlc.refund != self.public.to_bytes() {
+            return None;
+        }
+        // Com a sequence final o lock_time seria ignorado, e CHECKLOCKTIMEVERIFY recusa
+        let input = Input { sequence: SEQUENCE_FINAL - 1, ..Input::new(prev_tx_hash, output_index) };
+        self.spend_htlc(input, htlc.timeout, spent, to, fee, vec![Op::PushInt(0)])
+    }
+
+    /// Envia o valor do HTLC (menos a taxa) para `to`, com a assinatura
+    /// seguida de `branch` no desbloqueio
+    fn spend_htlc(&self, input: Input, lock_time: u64, spent: &Output,
+                  to: &Address, fee: u64, branch: Vec<Op>) -> Option<Transaction> {
+        let value = spent.value.checked_sub(fee)?;
+        let mut tx = Transaction::new(vec![input], vec![Output::to_address(value, to)]);
+        tx.lock_time = lock_time;
+        let signature = tx.signature_for(0, spent, &self.secret)?;
+        let mut unlocking = vec![Op::PushData(signature)];
+        unlocking.extend(branch);
+        tx.inputs[0].script_sig = Script::new(unlocking);
+        Some(tx)
+    }
+}
+
+/// Transação sem assinaturas que gasta um output multisig
+///
+/// Circula entre os donos, cada um chamando `Wallet::cosign_multisig`
+pub fn build_multisig_spend(prev_tx_hash: Hash, output_index: usize, outputs: Vec<Output>) -> Transaction {
+    Transaction::new(vec![Input::new(prev_tx_hash, output_index)], outputs)
+}
+
+fn signature_matches(pubkey: &[u8], signature: &[u8], data: &[u8]) -> bool {
+    let Ok(key_bytes) = <[u8; 32]>::try_from(pubkey) else { return false; };
+    let Ok(public_key) = VerifyingKey::from_bytes(&key_bytes) else { return false; };
+    let Ok(signature) = Signature::from_slice(signature) else { return false; };
+    public_key.verify(data, &signature).is_ok()
+}
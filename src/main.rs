@@ -1,52 +1,40 @@
 use std::env;
-use tokio::net::{TcpListener};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use utxo_blockchain::models::blockchain::Blockchain;
+use utxo_blockchain::models::chain_params::ChainParams;
+use utxo_blockchain::crypto::address::Address;
 use utxo_blockchain::crypto::wallet::Wallet;
-use utxo_blockchain::models::block::Block;
-use utxo_blockchain::models::transaction::Transaction;
+use utxo_blockchain::network::server::Node;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Configuração de Argumentos (Ex: cargo run 8080)
+    // Configuração de Argumentos (Ex: cargo run 8080 regtest <endereço do minerador>)
     let args: Vec<String> = env::args().collect();
     let port = args.get(1).map(|s| s.as_str()).unwrap_or("8080");
+    let network = args.get(2).map(|s| s.as_str()).unwrap_or("mainnet");
     let addr = format!("127.0.0.1:{}", port);
+    let params = ChainParams::from_name(network).ok_or("Rede desconhecida (use mainnet, testnet ou regtest)")?;
 
-    println!("🚀 Iniciando Nó na porta {}...", port);
+    println!("🚀 Iniciando Nó na porta {} ({})...", port, network);
 
     // Inicializar Blockchain e Carteira
-    let miner_wallet = Wallet::from_seed("minerador_secreto_123");
-    let mut blockchain = carregar_ou_criar_blockchain("blockchain.json", &miner_wallet);
+    // Um endereço digitado errado ou de outra rede para o nó antes de minerar
+    let miner_address = match args.get(3) {
+        Some(text) => Address::parse(text, params.network)
+            .map_err(|e| format!("Endereço do minerador inválido: {}", e))?,
+        None => Wallet::from_seed("minerador_secreto_123").address(params.network),
+    };
+    println!("💰 Recompensas vão para {}", miner_address);
+    let mut blockchain = carregar_ou_criar_blockchain("blockchain.json", params)?;
 
     // CLONAR ESTADO PARA AS THREADS
     // Nota: Em um sistema real devo usar Arc<Mutex<Blockchain>>, 
     // mas para este rascunho vamos focar na escuta de rede.
-    
-    let addr_clone = addr.clone();
-    
-    //  O SERVIDOR DE REDE (Escuta outros nós)
-    tokio::spawn(async move {
-        let listener = TcpListener::bind(&addr_clone).await.unwrap();
-        println!("📡 Servidor P2P ouvindo em {}", addr_clone);
+    let node = Node::new(addr, &blockchain);
 
-        loop {
-            let (mut socket, peer_addr) = listener.accept().await.unwrap();
-            println!("🤝 Novo peer conectado: {}", peer_addr);
-
-            tokio::spawn(async move {
-                let mut buffer = [0; 1024];
-                loop {
-                    let n = socket.read(&mut buffer).await.unwrap();
-                    if n == 0 { break; }
-                    
-                    let recebido = String::from_utf8_lossy(&buffer[..n]);
-                    println!("📩 Mensagem de {}: {}", peer_addr, recebido);
-                    
-                    // Responder apenas para confirmar
-                    socket.write_all(b"Mensagem recebida pelo no!").await.unwrap();
-                }
-            });
+    //  O SERVIDOR DE REDE (Escuta outros nós, só da mesma rede e gênesis)
+    tokio::spawn(async move {
+        if let Err(e) = node.start_server().await {
+            println!("❌ Servidor P2P parou: {}", e);
         }
     });
 
@@ -58,12 +46,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         if input.trim() == "q" { break; }
 
         println!("⛏️  Criando e minerando novo bloco...");
-        let mut novo_bloco = blockchain.create_next_block(&miner_wallet.address(), 16);
+        let mut novo_bloco = blockchain.create_next_block(&miner_address);
         novo_bloco.mine();
 
-        if blockchain.add_block(novo_bloco) {
-            blockchain.save_to_file("blockchain.json")?;
-            println!("✅ Bloco minerado e salvo!");
+        match blockchain.add_block(novo_bloco) {
+            Ok(_) => {
+                blockchain.save_to_file("blockchain.json")?;
+                println!("✅ Bloco minerado e salvo!");
+            }
+            Err(e) => println!("❌ Bloco rejeitado: {}", e),
         }
         
         input.clear();
@@ -73,14 +64,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 // Função auxiliar para simplificar a main
-fn carregar_ou_criar_blockchain(path: &str, wallet: &Wallet) -> Blockchain {
+fn carregar_ou_criar_blockchain(path: &str, params: ChainParams) -> std::io::Result<Blockchain> {
     if std::path::Path::new(path).exists() {
-        Blockchain::load_from_file(path).unwrap()
+        Blockchain::load_from_file(path, params)
     } else {
-        let genesis_tx = Transaction::coinbase(&wallet.address(), 50);
-        let genesis_block = Block::genesis(genesis_tx);
-        let bc = Blockchain::new(genesis_block);
-        bc.save_to_file(path).unwrap();
-        bc
+        // Todo nó começa do mesmo gênesis fixo da rede
+        let bc = Blockchain::new(params);
+        bc.save_to_file(path)?;
+        Ok(bc)
     }
-}
\ No newline at end of file
+}
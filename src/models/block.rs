@@ -22,6 +22,15 @@ impl BlockHeader {
     pub fn calculate_hash(&self) -> Hash {
         Hash::hash_data_cached(self)
     }
+
+    /// Trabalho esperado para minerar este header (2^dificuldade tentativas)
+    ///
+    /// Usado para comparar branches: vence o de maior trabalho acumulado
+    pub fn work(&self) -> u128 {
+        u32::try_from(self.difficulty).ok()
+            .and_then(|bits| 1u128.checked_shl(bits))
+            .unwrap_or(u128::MAX)
+    }
 }
 
 impl Block {
@@ -43,6 +52,11 @@ impl Block {
         }
     }
 
+    /// Tamanho em bytes do bloco serializado
+    pub fn serialized_size(&self) -> usize {
+        bincode::serialized_size(self).expect("Falha ao serializar com bincode") as usize
+    }
+
     pub fn mine(&mut self){
         let start = Utc::now();
 
@@ -75,16 +89,16 @@ impl Block {
         }
     }
 
-    pub fn genesis(coinbase_tx: Transaction) -> Self {
+    pub fn genesis(coinbase_tx: Transaction, timestamp: i64, difficulty: usize, nonce: u64) -> Self {
         let transactions = vec![coinbase_tx];
         let merkle_root = Hash::calculate_merkle_root(&transactions);
 
         let header = BlockHeader {
-            timestamp: Utc::now().timestamp(),
+            timestamp,
             prev_block_hash: Hash::new_empty(),
             merkle_root,
-            nonce: 0,
-            difficulty: 0,
+            nonce,
+            difficulty,
         };
         Block { header, transactions }
     }
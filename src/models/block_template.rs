@@ -0,0 +1,196 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use crate::models::blockchain::Blockchain;
+use crate::models::block::Block;
+use crate::models::transaction::{Output, Transaction};
+use crate::models::utxo_view::UtxoView;
+use crate::crypto::address::Address;
+use crate::crypto::hashing::Hash;
+
+/// Bloco pronto para minerar, com o resumo do que ele paga
+#[derive(Debug, Clone)]
+pub struct BlockTemplate {
+    pub block: Block,
+    pub total_fees: u64,
+    pub reward: u64,                    // Subsídio da altura + taxas
+    pub size: usize,                    // Bytes do bloco serializado
+    pub selected: Vec<Hash>,            // Transações da mempool incluídas, na ordem do bloco
+    pub rejected: Vec<Hash>,            // Transações da mempool que falharam na validação
+}
+
+/// Transação da mempool candidata a entrar no bloco
+struct Candidate<'a> {
+    tx: &'a Transaction,
+    fee: u64,
+    size: usize,
+    ancestors: HashSet<Hash>,           // Ancestrais na mempool que ainda não entraram no bloco
+    package_fee: u64,                   // Ela mais `ancestors`
+    package_size: usize,
+}
+
+impl Candidate<'_> {
+    fn score(&self, txid: Hash) -> PackageScore {
+        PackageScore { fee: self.package_fee, size: self.package_size, txid }
+    }
+}
+
+/// Taxa por byte de um pacote, para a fila de prioridade
+///
+/// Quando um ancestral entra no bloco, os descendentes voltam para a fila
+/// com a pontuação nova; a entrada antiga fica lá e é ignorada ao sair
+#[derive(PartialEq, Eq)]
+struct PackageScore {
+    fee: u64,
+    size: usize,
+    txid: Hash,
+}
+
+impl Ord for PackageScore {
+    /// Compara fee/size sem divisão: a/b > c/d  <=>  a*d > c*b. No empate, menor txid primeiro
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.fee as u128 * other.size as u128).cmp(&(other.fee as u128 * self.size as u128))
+            .then_with(|| other.txid.cmp(&self.txid))
+            .then_with(|| other.size.cmp(&self.size))
+    }
+}
+
+impl PartialOrd for PackageScore {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Blockchain {
+
+    /// Monta um bloco escolhendo as transações que pagam mais por byte
+    ///
+    /// A pontuação de cada transação considera o pacote com seus ancestrais
+    /// ainda não incluídos (taxa total / tamanho total), então um filho que
+    /// paga bem puxa o pai junto (child-pays-for-parent). A mempool não é alterada.
+    /// Retorna `None` se a soma dos pesos é zero (ninguém para receber)
+    pub fn build_block_template(&self, payouts: &[(&Address, u64)]) -> Option<BlockTemplate> {
+        let height = self.chain.len() as u64;
+        let prev_hash = self.tip_hash();
+
+        // O valor dos outputs não muda o tamanho serializado, então dá para
+        // medir o bloco só com a coinbase antes de saber as taxas
+        let coinbase = Transaction::coinbase_split(split_reward(0, payouts)?, height, Vec::new());
+        let mut block_size = Block::new(prev_hash, vec![coinbase], 0).serialized_size();
+
+        let mut candidates = self.block_candidates();
+        let mut queue: BinaryHeap<PackageScore> = candidates.iter()
+            .map(|(txid, candidate)| candidate.score(*txid))
+            .collect();
+        let mut selected: Vec<Hash> = Vec::new();
+        let mut included: HashSet<Hash> = HashSet::new();
+        let mut rejected: HashSet<Hash> = HashSet::new();
+        let mut transactions = Vec::new();
+        let mut total_fees = 0;
+        let mut view = UtxoView::new(&self.utxos, height);
+
+        // Sempre o pacote de maior taxa por byte; quem não cabe agora só volta
+        // para a fila se um ancestral entrar e o pacote dele encolher
+        while let Some(best) = queue.pop() {
+            if included.contains(&best.txid) || rejected.contains(&best.txid) {
+                continue;
+            }
+            let candidate = &candidates[&best.txid];
+            if (candidate.package_fee, candidate.package_size) != (best.fee, best.size) {
+                continue;
+            }
+            let fits = block_size + best.size <= self.params.max_block_size
+                && transactions.len() + candidate.ancestors.len() + 1 < self.params.max_block_transactions;
+            if !fits {
+                continue;
+            }
+
+            // Pais antes dos filhos: um ancestral sempre tem menos ancestrais pendentes
+            let mut package: Vec<Hash> = candidate.ancestors.iter().copied().chain([best.txid]).collect();
+            package.sort_by_key(|txid| (candidates[txid].ancestors.len(), *txid));
+
+            // Cada um é validado contra a visão do bloco
+            for txid in package {
+                let Candidate { tx, fee, size, .. } = candidates[&txid];
+                if self.validate_transaction_in(tx, &view).is_err() {
+                    // Quem depende dela também não pode entrar
+                    rejected.extend(self.mempool.descendants(&txid));
+                    break;
+                }
+                total_fees += fee;
+                block_size += size;
+                view.apply(tx);
+                included.insert(txid);
+                selected.push(txid);
+                transactions.push(tx.clone());
+
+                for descendant in self.mempool.descendants(&txid).into_iter().skip(1) {
+                    let Some(entry) = candidates.get_mut(&descendant) else { continue; };
+                    if entry.ancestors.remove(&txid) {
+                        entry.package_fee -= fee;
+                        entry.package_size -= size;
+                        queue.push(entry.score(descendant));
+                    }
+                }
+            }
+        }
+
+        // Recompensa do minerador (subsídio da altura + taxas)
+        let reward = self.subsidy_at(height) + total_fees;
+        let coinbase = Transaction::coinbase_split(split_reward(reward, payouts)?, height, Vec::new());
+        transactions.insert(0, coinbase);
+
+        let mut block = Block::new(prev_hash, transactions, self.next_difficulty());
+        // O timestamp precisa passar do median-time-past mesmo que o relógio atrase
+        block.header.timestamp = (self.clock)().max(self.median_time_past() + 1);
+
+        Some(BlockTemplate {
+            size: block.serialized_size(),
+            block,
+            total_fees,
+            reward,
+            selected,
+            rejected: rejected.into_iter().collect(),
+        })
+    }
+
+    /// Taxa, tamanho e pacote de ancestrais de cada transação da mempool
+    fn block_candidates(&self) -> HashMap<Hash, Candidate<'_>> {
+        self.mempool.iter()
+            .map(|(tx_hash, entry)| {
+                let ancestors: HashSet<Hash> = self.mempool.ancestors(tx_hash).into_iter()
+                    .filter(|hash| hash != tx_hash)
+                    .collect();
+                let package: Vec<_> = ancestors.iter().filter_map(|hash| self.mempool.get(hash)).collect();
+                (*tx_hash, Candidate {
+                    tx: &entry.tx,
+                    fee: entry.fee,
+                    size: entry.size,
+                    package_fee: entry.fee + package.iter().map(|a| a.fee).sum::<u64>(),
+                    package_size: entry.size + package.iter().map(|a| a.size).sum::<usize>(),
+                    ancestors,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Reparte `reward` proporcionalmente aos pesos, sem perder nenhuma unidade
+///
+/// `None` se a soma dos pesos é zero: uma coinbase sem outputs jogaria a recompensa fora
+pub(crate) fn split_reward(reward: u64, payouts: &[(&Address, u64)]) -> Option<Vec<Output>> {
+    let total_weight: u128 = payouts.iter().map(|(_, weight)| *weight as u128).sum();
+    if total_weight == 0 {
+        return None;
+    }
+
+    let mut outputs: Vec<Output> = payouts.iter()
+        .filter(|(_, weight)| *weight > 0)
+        .map(|(address, weight)| {
+            Output::to_address((reward as u128 * *weight as u128 / total_weight) as u64, address)
+        })
+        .collect();
+
+    let distributed: u64 = outputs.iter().map(|o| o.value).sum();
+    outputs[0].value += reward - distributed;
+    Some(outputs)
+}
@@ -1,8 +1,10 @@
-use std::fs::File;
-use std::io::{Write, Read};
-use ed25519_dalek::VerifyingKey;
 use serde::{Deserialize, Serialize};
-use crate::models::{block::Block, transaction::{Output, Transaction}};
+use crate::models::{block::Block, chain_fork::BlockIndexEntry, chain_undo::BlockUndo, chain_params::ChainParams, mempool::{Mempool, MempoolAcceptance, MempoolEntry, MempoolPolicy}, transaction::{Output, Transaction}};
+use crate::models::chain_validation::{Clock, system_clock};
+use crate::models::errors::{BlockValidationError, TxValidationError};
+use crate::models::script::Script;
+use crate::models::utxo_view::UtxoView;
+use crate::crypto::address::Address;
 use crate::crypto::hashing::Hash;
 use std::collections::HashMap;
 
@@ -13,89 +15,180 @@ pub struct UTXOKey{
     pub output_index: usize,
 }
 
+/// Um output não gasto junto com a altura do bloco que o criou
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UtxoEntry {
+    pub output: Output,
+    pub height: u64,
+    pub is_coinbase: bool,              // Recompensas só podem ser gastas após amadurecer
+}
+
 pub struct Blockchain {
-    pub chain: Vec<Block>,
-    pub utxos: HashMap<UTXOKey, Output>,
-    pub mempool: Vec<Transaction>,          // Sala de espera
+    pub chain: Vec<Block>,                              // Branch ativo (do gênesis até a ponta)
+    pub utxos: HashMap<UTXOKey, UtxoEntry>,
+    pub mempool: Mempool,                               // Sala de espera
+    pub block_index: HashMap<Hash, BlockIndexEntry>,    // Árvore com todos os blocos conhecidos
+    pub undo: Vec<BlockUndo>,                           // Um registro por bloco de `chain`
+    pub params: ChainParams,                            // Regras de consenso da rede
+    pub clock: Clock,                                   // Relógio do nó (injetável em testes)
 }
 
 // Estrutura para salvar o estado completo
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BlockchainSnapshot {
     pub chain: Vec<Block>,
-    pub utxos: Vec<(UTXOKey, Output)>,
-    pub mempool: Vec<Transaction>,
+    pub utxos: Vec<(UTXOKey, UtxoEntry)>,
+    pub mempool: Vec<(Transaction, i64)>,              // Na ordem de chegada, com o horário de entrada
+    #[serde(default)]
+    pub side_blocks: Vec<Block>,                        // Blocos fora do branch ativo
+    #[serde(default)]
+    pub undo: Vec<BlockUndo>,
 }
 
 
-pub const MINING_REWARD: u64 = 50;
 
 impl Blockchain {
 
 
-    /// Inicializa o blockchain com o block genesis
-    pub fn new(genesis_block: Block) -> Self {
-        let mut bc = Blockchain {
-            chain: Vec::new(),
-            utxos: HashMap::new(),
-            mempool: Vec::new(),
-        };
+    /// Inicializa o blockchain com o bloco gênesis fixo da rede
+    pub fn new(params: ChainParams) -> Self {
+        let genesis_block = params.genesis_block();
+        assert_eq!(genesis_block.header.calculate_hash(), params.genesis_hash(), "Gênesis construído difere do fixado");
+        let mut bc = Blockchain::empty(params);
         // Ao iniciar, processa o bloco gênesis para popular os primeiros UTXOs
-        bc.add_block(genesis_block);
+        bc.add_block(genesis_block).expect("Gênesis da rede não passa na validação");
         bc
     }
 
-    
-    pub fn submit_transaction(&mut self, tx: Transaction) -> bool {
-        if self.validate_transaction(&tx).is_err() {
-            return false;
+    /// Estado vazio, sem nem o gênesis
+    pub(crate) fn empty(params: ChainParams) -> Self {
+        Blockchain {
+            chain: Vec::new(),
+            utxos: HashMap::new(),
+            mempool: Mempool::new(MempoolPolicy::default()),
+            block_index: HashMap::new(),
+            undo: Vec::new(),
+            params,
+            clock: system_clock(),
+        }
+    }
+
+    /// Valida a transação e coloca na mempool
+    ///
+    /// Além das regras de consenso, aplica a política local: taxa mínima
+    /// de relay, replace-by-fee e limite de memória. Transações expiradas
+    /// saem antes. Retorna o que foi substituído ou despejado para ela entrar
+    pub fn submit_transaction(&mut self, tx: Transaction) -> Result<MempoolAcceptance, TxValidationError> {
+        self.expire_mempool();
+        let now = (self.clock)();
+        self.submit_transaction_at(tx, now)
+    }
+
+    /// Como `submit_transaction`, mas com o horário de entrada dado e sem expirar nada
+    ///
+    /// Usado ao recarregar a mempool do disco, para não reiniciar a contagem da expiração
+    pub(crate) fn submit_transaction_at(&mut self, tx: Transaction, time: i64) -> Result<MempoolAcceptance, TxValidationError> {
+        if tx.is_coinbase() {
+            return Err(TxValidationError::UnexpectedCoinbase);
         }
 
         // Verifica se a transação já não está na mempool evitando spam
         let tx_hash = tx.calculate_hash();
-        if self.mempool.iter().any(|m_tx| m_tx.calculate_hash() == tx_hash) {
-            println!("Transacao ja esta na mempool");
-            return false;
+        if self.mempool.contains(&tx_hash) {
+            return Err(TxValidationError::AlreadyInMempool { tx_hash });
         }
-        self.mempool.push(tx);
-        true
-    }
+        // Inputs podem gastar outputs confirmados ou de outras transações da mempool
+        let fee = self.validate_transaction_in(&tx, &self.mempool_view())?;
 
-    /// O Minerador "limpa" a mempool e cria um novo bloco
-    pub fn create_next_block(&mut self, miner_addr: &str, difficulty: usize) -> Block {
-        
-        let mut total_fees = 0;
-        let mut valid_txs = Vec::new();
-
-        // Dreana a mempool e calcula a taxa
-        let mempool_txs: Vec<_> = self.mempool.drain(..).collect();
-        for tx in mempool_txs {
-            if let Ok(fee) = self.validate_transaction(&tx) {
-                total_fees += fee;
-                valid_txs.push(tx);
+        let size = tx.serialized_size();
+        let required = self.mempool.policy.min_relay_fee(size);
+        if fee < required {
+            return Err(TxValidationError::FeeTooLow { fee, required });
+        }
+
+        // Duas transações da mempool não podem gastar o mesmo output,
+        // a não ser que a nova substitua as antigas pagando mais
+        let replaced_hashes = self.mempool.replacement_set(&tx, fee, size)?;
+        let replaced: Vec<_> = replaced_hashes.iter()
+            .filter_map(|hash| self.mempool.remove(hash))
+            .collect();
+
+        match self.mempool.insert(tx, fee, time) {
+            Ok(evicted) => Ok(MempoolAcceptance { tx_hash, fee, replaced: replaced_hashes, evicted }),
+            Err(error) => {
+                // Não entrou: as substituídas voltam
+                self.mempool.restore(replaced);
+                Err(error)
             }
         }
+    }
 
-        // Recompensa do minerador (50 + taxa)
-        let coinbase_reward = MINING_REWARD + total_fees;
-        let mut transactions = vec![Transaction::coinbase(miner_addr, coinbase_reward)];
-        transactions.extend(valid_txs);
+    /// Descarta o que está na mempool há mais de `policy.expiry`, pelo relógio do nó
+    ///
+    /// Roda ao receber transações, ao montar e ao conectar blocos, então
+    /// mesmo um nó que só minera não acumula transações esquecidas
+    pub fn expire_mempool(&mut self) -> Vec<Hash> {
+        let now = (self.clock)();
+        self.mempool.expire(now)
+    }
 
-        let prev_hash = self.chain.last()
-                .map(|b| b.header.calculate_hash()).unwrap_or(Hash::new_empty());
-        
-        Block::new(prev_hash, transactions, difficulty)
+    /// UTXO set confirmado mais os outputs criados pela mempool
+    ///
+    /// Os outputs não confirmados ficam na altura do próximo bloco
+    pub fn mempool_view(&self) -> UtxoView<'_> {
+        let mut view = UtxoView::new(&self.utxos, self.chain.len() as u64);
+        for (_, entry) in self.mempool.iter() {
+            view.add_outputs(&entry.tx);
+        }
+        view
     }
 
-    
+    /// O Minerador "limpa" a mempool e cria um novo bloco
+    pub fn create_next_block(&mut self, miner_addr: &Address) -> Block {
+        self.create_next_block_with_payouts(&[(miner_addr, 1)])
+            .expect("Peso 1 nunca soma zero")
+    }
 
-    //Adiciona um bloco à corrente e atualiza o UTXO set
-    pub fn add_block(&mut self, block: Block) -> bool {
-        
-        if !self.validate_block(&block) {
-            return false;
+    /// Cria o próximo bloco dividindo a recompensa entre vários endereços
+    ///
+    /// Cada par é (endereço, peso); a recompensa é repartida na proporção
+    /// dos pesos e o resto da divisão inteira vai para o primeiro endereço.
+    /// As transações escolhidas (e as inválidas) saem da mempool, as demais ficam.
+    /// Com peso total zero retorna `None` e não mexe na mempool
+    pub fn create_next_block_with_payouts(&mut self, payouts: &[(&Address, u64)]) -> Option<Block> {
+        self.expire_mempool();
+        let template = self.build_block_template(payouts)?;
+
+        for tx_hash in &template.selected {
+            self.mempool.remove(tx_hash);
         }
-        
+        for tx_hash in &template.rejected {
+            self.mempool.remove_with_descendants(tx_hash);
+        }
+
+        Some(template.block)
+    }
+
+    /// Conecta um bloco no topo do branch ativo e atualiza o UTXO set
+    ///
+    /// Quem decide *qual* branch é o ativo é o `add_block` (ver `chain_fork.rs`).
+    /// Retorna as transações que saíram da mempool por estarem no bloco ou em conflito com ele
+    pub(crate) fn connect_block(&mut self, block: Block) -> Result<Vec<MempoolEntry>, BlockValidationError> {
+        self.validate_block(&block)?;
+        let undo = self.apply_block_utxos(&block, self.chain.len() as u64);
+        let purged = self.mempool.remove_for_block(&block);
+        self.expire_mempool();
+        // Inseri o Bloco na corrente
+        self.chain.push(block);
+        self.undo.push(undo);
+        Ok(purged)
+    }
+
+    /// Aplica as transações de um bloco ao UTXO set
+    ///
+    /// Retorna o registro de undo que permite desfazer exatamente estas mudanças
+    pub(crate) fn apply_block_utxos(&mut self, block: &Block, height: u64) -> BlockUndo {
+        let mut undo = BlockUndo::default();
         for tx in &block.transactions {
             let tx_hash = tx.calculate_hash();
 
@@ -107,7 +200,9 @@ impl Blockchain {
                         tx_hash: input.prev_tx_hash,
                         output_index: input.output_index,
                     };
-                    self.utxos.remove(&key);
+                    if let Some(spent) = self.utxos.remove(&key) {
+                        undo.spent.push((key, spent));
+                    }
                 }
             }
             // Adicionar novos Outputs gerados por esta transação como UTXOs
@@ -116,24 +211,27 @@ impl Blockchain {
                     tx_hash,
                     output_index: index, 
                 };
-                self.utxos.insert(key, output.clone());
+                let entry = UtxoEntry { output: output.clone(), height, is_coinbase: tx.is_coinbase() };
+                self.utxos.insert(key.clone(), entry);
+                undo.created.push(key);
             }
         }
-        // Inseri o Bloco na corrente
-        self.chain.push(block);
-        true
-    } 
+        undo
+    }
 
-    /// Calcula o saldo de um endereço (chave pública em hex) 
-    pub fn get_balance(&self, address: &str) -> u64 {
+    /// Calcula o saldo de um endereço
+    ///
+    /// Conta só os outputs pagos diretamente ao endereço (`pay_to_pubkey_hash`)
+    pub fn get_balance(&self, address: &Address) -> u64 {
+        let script = Script::pay_to_pubkey_hash(&address.pubkey_hash);
         let mut balance = 0;
         // Percorre somente os UTXOs existentes (moedas não gastas)
-        for output in self.utxos.values() {
-            if output.pubkey == address {
-                balance += output.value;
+        for entry in self.utxos.values() {
+            if entry.output.script_pubkey == script {
+                balance += entry.output.value;
             }
         } balance
     }
 
 
-}
\ No newline at end of file
+}
@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+use crate::models::blockchain::Blockchain;
+use crate::models::block::{Block, BlockHeader};
+use crate::models::errors::BlockValidationError;
+use crate::crypto::hashing::Hash;
+
+/// Entrada da árvore de blocos: o bloco e sua posição no branch a que pertence
+#[derive(Debug, Clone)]
+pub struct BlockIndexEntry {
+    pub block: Block,
+    pub height: u64,
+    pub cumulative_work: u128,          // Soma do trabalho do gênesis até este bloco
+}
+
+/// Resultado de `add_block`: quais blocos saíram e entraram no branch ativo
+/// e o que isso mudou na mempool
+///
+/// Um bloco guardado num branch lateral (ou já conhecido) gera listas vazias
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChainUpdate {
+    pub disconnected: Vec<Hash>,        // Do antigo topo até o ponto de bifurcação
+    pub connected: Vec<Hash>,           // Do ponto de bifurcação até o novo topo
+    pub resubmitted: usize,             // Transações dos blocos desconectados que voltaram à mempool
+    pub dropped: usize,                 // Transações dos blocos desconectados que ficaram inválidas
+    pub purged: usize,                  // Saíram da mempool: confirmadas, em conflito ou inválidas no branch novo
+}
+
+impl ChainUpdate {
+    pub fn is_reorg(&self) -> bool {
+        !self.disconnected.is_empty()
+    }
+}
+
+impl Blockchain {
+
+    /// Recebe um bloco de qualquer branch
+    ///
+    /// O bloco é guardado na árvore e, se o seu branch passar a ter mais
+    /// trabalho acumulado que o ativo, a corrente é reorganizada para ele.
+    /// Blocos órfãos (pai desconhecido) são rejeitados
+    pub fn add_block(&mut self, block: Block) -> Result<ChainUpdate, BlockValidationError> {
+        let hash = block.header.calculate_hash();
+        if let Some(entry) = self.block_index.get(&hash) {
+            // Já conhecido: volta para o branch ativo se tiver mais trabalho
+            // que o topo (ex.: depois de um `disconnect_tip`)
+            if !self.is_in_active_chain(&hash) && entry.cumulative_work > self.tip_work() {
+                return self.reorganize_to(hash);
+            }
+            return Ok(ChainUpdate::default());
+        }
+        // Blocos grandes demais são descartados antes de entrar na árvore
+        self.validate_block_limits(&block)?;
+        // A árvore tem uma única raiz: o gênesis fixo da rede
+        if block.header.prev_block_hash.is_empty() && hash != self.params.genesis_hash() {
+            return Err(BlockValidationError::UnexpectedGenesis { hash });
+        }
+
+        // Gênesis: não há branch para escolher, conecta direto
+        if self.chain.is_empty() {
+            self.connect_block(block.clone())?;
+            self.index_block(block);
+            return Ok(ChainUpdate { connected: vec![hash], ..Default::default() });
+        }
+
+        // Branches laterais também pagam o trabalho que declaram
+        self.validate_header(&block.header)?;
+
+        let prev_block_hash = block.header.prev_block_hash;
+        let work = self.index_block(block)
+            .ok_or(BlockValidationError::UnknownParent { prev_block_hash })?;
+        if work <= self.tip_work() {
+            // Em caso de empate o primeiro branch visto continua ativo
+            return Ok(ChainUpdate::default());
+        }
+        self.reorganize_to(hash)
+    }
+
+    /// Checa um header contra o pai dele, em qualquer branch, antes de guardar o bloco
+    ///
+    /// Sem isso um bloco lateral poderia declarar dificuldade 0 e entrar na
+    /// árvore (e no disco) sem custar trabalho nenhum
+    fn validate_header(&self, header: &BlockHeader) -> Result<(), BlockValidationError> {
+        let parent = header.prev_block_hash;
+        let expected = self.difficulty_after(&parent)
+            .ok_or(BlockValidationError::UnknownParent { prev_block_hash: parent })?;
+        if header.difficulty != expected {
+            return Err(BlockValidationError::WrongDifficulty { expected, found: header.difficulty });
+        }
+
+        let hash = header.calculate_hash();
+        if !hash.has_sufficient_difficulty(header.difficulty) {
+            return Err(BlockValidationError::InsufficientProofOfWork { hash, difficulty: header.difficulty });
+        }
+        self.validate_timestamp_after(header, &parent)
+    }
+
+    /// Headers de `from` e dos seus ancestrais, do mais novo para o mais velho,
+    /// até `count` deles
+    pub(crate) fn ancestor_headers(&self, from: &Hash, count: usize) -> Vec<&BlockHeader> {
+        let mut headers = Vec::new();
+        let mut cursor = *from;
+        while headers.len() < count {
+            let Some(entry) = self.block_index.get(&cursor) else { break; };
+            headers.push(&entry.block.header);
+            cursor = entry.block.header.prev_block_hash;
+        }
+        headers
+    }
+
+    /// Insere um bloco na árvore calculando altura e trabalho a partir do pai
+    ///
+    /// Retorna o trabalho acumulado, ou `None` se o pai não for conhecido
+    /// (um bloco sem pai só entra se for o gênesis da rede)
+    pub(crate) fn index_block(&mut self, block: Block) -> Option<u128> {
+        let hash = block.header.calculate_hash();
+        let (height, cumulative_work) = if block.header.prev_block_hash.is_empty() {
+            if hash != self.params.genesis_hash() {
+                return None;
+            }
+            (0, block.header.work())
+        } else {
+            let parent = self.block_index.get(&block.header.prev_block_hash)?;
+            (parent.height + 1, parent.cumulative_work.saturating_add(block.header.work()))
+        };
+
+        self.block_index.insert(hash, BlockIndexEntry { block, height, cumulative_work });
+        Some(cumulative_work)
+    }
+
+    pub fn tip_hash(&self) -> Hash {
+        self.chain.last()
+            .map(|b| b.header.calculate_hash()).unwrap_or(Hash::new_empty())
+    }
+
+    /// Trabalho acumulado do branch ativo
+    pub fn tip_work(&self) -> u128 {
+        self.block_index.get(&self.tip_hash())
+            .map(|e| e.cumulative_work).unwrap_or(0)
+    }
+
+    pub fn is_in_active_chain(&self, hash: &Hash) -> bool {
+        match self.block_index.get(hash) {
+            Some(entry) => self.chain.get(entry.height as usize)
+                .is_some_and(|b| b.header.calculate_hash() == *hash),
+            None => false,
+        }
+    }
+
+    /// Troca o branch ativo para o que termina em `new_tip`
+    ///
+    /// Desfaz os blocos acima do ponto de bifurcação e conecta os do novo
+    /// branch validando um a um. Se algum for inválido, ele e seus
+    /// descendentes são descartados da árvore e o branch antigo é restaurado,
+    /// junto com o que o branch novo tinha tirado da mempool.
+    /// No sucesso, as transações dos blocos desconectados voltam para a mempool
+    fn reorganize_to(&mut self, new_tip: Hash) -> Result<ChainUpdate, BlockValidationError> {
+        // Caminha do novo topo até encontrar um bloco do branch ativo
+        let mut branch = Vec::new();
+        let mut blocks = Vec::new();
+        let mut cursor = new_tip;
+        while !self.is_in_active_chain(&cursor) {
+            let entry = self.block_index.get(&cursor)
+                .ok_or(BlockValidationError::UnknownParent { prev_block_hash: cursor })?;
+            branch.push(cursor);
+            blocks.push(entry.block.clone());
+            cursor = entry.block.header.prev_block_hash;
+        }
+        branch.reverse();
+        blocks.reverse();
+
+        let fork_height = self.block_index.get(&cursor)
+            .ok_or(BlockValidationError::UnknownParent { prev_block_hash: cursor })?
+            .height as usize;
+        let mut old_blocks = Vec::new();
+        while self.chain.len() > fork_height + 1 {
+            old_blocks.extend(self.undo_tip());
+        }
+
+        let mut purged = Vec::new();
+        for (hash, block) in branch.iter().zip(blocks.iter().cloned()) {
+            let error = match self.connect_block(block) {
+                Ok(removed) => {
+                    purged.extend(removed);
+                    continue;
+                }
+                Err(error) => error,
+            };
+
+            self.discard_branch(hash);
+            while self.chain.len() > fork_height + 1 {
+                self.undo_tip();
+            }
+            // Os blocos antigos já foram validados nesta mesma base
+            for block in old_blocks.into_iter().rev() {
+                self.connect_block(block).expect("Bloco do branch antigo deixou de ser válido");
+            }
+            // O que os blocos do branch novo confirmaram ou derrubaram volta
+            self.resubmit_purged(purged);
+            return Err(error);
+        }
+
+        let mut update = ChainUpdate {
+            disconnected: old_blocks.iter().map(|b| b.header.calculate_hash()).collect(),
+            connected: branch,
+            purged: purged.len(),
+            ..Default::default()
+        };
+        if !old_blocks.is_empty() {
+            let confirmed: HashSet<Hash> = blocks.iter()
+                .flat_map(|block| &block.transactions)
+                .map(|tx| tx.calculate_hash())
+                .collect();
+            (update.resubmitted, update.dropped) = self.resubmit_disconnected(&old_blocks, &confirmed);
+            update.purged += self.remove_invalid_from_mempool();
+        }
+        Ok(update)
+    }
+
+    /// Remove da árvore um bloco inválido e todos os blocos construídos sobre ele
+    fn discard_branch(&mut self, root: &Hash) {
+        let mut removed = HashSet::from([*root]);
+        loop {
+            let children: Vec<Hash> = self.block_index.iter()
+                .filter(|(h, e)| !removed.contains(*h) && removed.contains(&e.block.header.prev_block_hash))
+                .map(|(h, _)| *h)
+                .collect();
+            if children.is_empty() { break; }
+            removed.extend(children);
+        }
+        self.block_index.retain(|h, _| !removed.contains(h));
+    }
+
+    /// Blocos conhecidos que não fazem parte do branch ativo, em ordem de altura
+    pub fn side_blocks(&self) -> Vec<Block> {
+        let mut side: Vec<&BlockIndexEntry> = self.block_index.iter()
+            .filter(|(h, _)| !self.is_in_active_chain(h))
+            .map(|(_, e)| e)
+            .collect();
+        side.sort_by_key(|e| e.height);
+        side.into_iter().map(|e| e.block.clone()).collect()
+    }
+}
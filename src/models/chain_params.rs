@@ -0,0 +1,187 @@
+use crate::models::block::Block;
+use crate::models::difficulty::RetargetParams;
+use crate::models::emission::EmissionSchedule;
+use crate::models::script::ScriptLimits;
+use crate::models::chain_validation::TimestampRules;
+use crate::models::transaction::Transaction;
+use crate::crypto::address::Address;
+use crate::crypto::hashing::Hash;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Regtest,                            // Rede local para testes: mineração instantânea
+}
+
+impl Network {
+    /// Primeiro byte dos endereços da rede, para não enviar moedas para a rede errada
+    ///
+    /// Diferentes dos do Bitcoin, para um endereço de lá não ser aceito aqui
+    pub fn address_version(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x44,
+            Network::Testnet => 0x7f,
+            Network::Regtest => 0x3c,
+        }
+    }
+}
+
+/// Definição fixa do bloco gênesis
+///
+/// Todos os nós da mesma rede constroem exatamente o mesmo gênesis,
+/// então o hash dele identifica a rede. O hash fica fixado em `hash`: uma
+/// mudança que altere o bloco construído é pega antes de chegar aos peers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisParams {
+    pub timestamp: i64,
+    pub reward_pubkey: &'static str,    // Chave (hex) dona da recompensa do gênesis
+    pub message: &'static str,          // Vai nos dados extras da coinbase
+    pub difficulty: usize,              // Também é a dificuldade inicial da corrente
+    pub nonce: u64,                     // Já minerado para `difficulty`
+    pub hash: &'static str,             // Hash (hex) esperado do bloco construído
+}
+
+/// Parâmetros de consenso de uma rede
+///
+/// Tudo que dois nós precisam concordar para aceitar os mesmos blocos
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainParams {
+    pub network: Network,
+    pub magic: [u8; 4],                 // Identifica as mensagens P2P desta rede (distinto do Bitcoin)
+    pub genesis: GenesisParams,
+    pub emission: EmissionSchedule,
+    pub retarget: RetargetParams,
+    pub timestamp_rules: TimestampRules,
+    pub coinbase_maturity: u64,         // Confirmações antes de gastar uma coinbase
+    pub max_block_size: usize,          // Bytes do bloco serializado
+    pub max_tx_size: usize,             // Bytes de uma transação serializada
+    pub max_block_transactions: usize,  // Incluindo a coinbase
+    pub script_limits: ScriptLimits,
+}
+
+const GENESIS_PUBKEY: &str = "d7fa15cdde18bd3b760c88a4e7e12a4f4917876e5e34afd6d6e247d3d13cb480";
+
+impl ChainParams {
+    pub fn mainnet() -> Self {
+        ChainParams {
+            network: Network::Mainnet,
+            magic: *b"utxo",
+            genesis: GenesisParams {
+                timestamp: 1769703282,
+                reward_pubkey: GENESIS_PUBKEY,
+                message: "utxo-blockchain mainnet genesis",
+                difficulty: 16,
+                nonce: 79273,
+                hash: "00006cae0396f262d12d6491c3c36d73720c49452fe484f3279113dc599d0e8d",
+            },
+            emission: EmissionSchedule {
+                initial_subsidy: 50,
+                halving_interval: 210_000,
+                tail_emission: 0,
+            },
+            retarget: RetargetParams {
+                window: 2016,
+                target_block_time: 600,
+                max_adjustment: 2,
+                min_difficulty: 16,
+                max_difficulty: 255,
+            },
+            timestamp_rules: TimestampRules {
+                median_window: 11,
+                max_future_drift: 2 * 60 * 60,
+            },
+            coinbase_maturity: 100,
+            max_block_size: 1_000_000,
+            max_tx_size: 100_000,
+            max_block_transactions: 10_000,
+            script_limits: ScriptLimits {
+                max_script_size: 10_000,
+                max_ops: 201,
+                max_stack_size: 1_000,
+                max_element_size: 520,
+                max_multisig_keys: 20,
+            },
+        }
+    }
+
+    pub fn testnet() -> Self {
+        ChainParams {
+            network: Network::Testnet,
+            magic: *b"utxt",
+            genesis: GenesisParams {
+                timestamp: 1769703282,
+                reward_pubkey: GENESIS_PUBKEY,
+                message: "utxo-blockchain testnet genesis",
+                difficulty: 8,
+                nonce: 18,
+                hash: "008eb9b9a258d28382cab4234449b5a1a533ca195698770d78d8345e87ecbddf",
+            },
+            retarget: RetargetParams {
+                window: 144,
+                target_block_time: 60,
+                max_adjustment: 2,
+                min_difficulty: 8,
+                max_difficulty: 255,
+            },
+            coinbase_maturity: 20,
+            ..Self::mainnet()
+        }
+    }
+
+    /// Dificuldade zero e sem retarget: blocos saem na hora
+    pub fn regtest() -> Self {
+        ChainParams {
+            network: Network::Regtest,
+            magic: *b"utxr",
+            genesis: GenesisParams {
+                timestamp: 1769703282,
+                reward_pubkey: GENESIS_PUBKEY,
+                message: "utxo-blockchain regtest genesis",
+                difficulty: 0,
+                nonce: 0,
+                hash: "d4c09e8d30dab21bef6a815b4fe24b58bb0eb0d2b6decc9e34c117c33800b490",
+            },
+            emission: EmissionSchedule {
+                halving_interval: 150,
+                ..Self::mainnet().emission
+            },
+            retarget: RetargetParams {
+                window: 0,                  // Desliga o ajuste
+                target_block_time: 600,
+                max_adjustment: 0,
+                min_difficulty: 0,
+                max_difficulty: 0,
+            },
+            ..Self::mainnet()
+        }
+    }
+
+    /// Preset pelo nome ("mainnet", "testnet" ou "regtest")
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mainnet" => Some(Self::mainnet()),
+            "testnet" => Some(Self::testnet()),
+            "regtest" => Some(Self::regtest()),
+            _ => None,
+        }
+    }
+
+    /// Constrói o bloco gênesis desta rede (sempre o mesmo)
+    pub fn genesis_block(&self) -> Block {
+        let genesis = &self.genesis;
+        let reward_pubkey = hex::decode(genesis.reward_pubkey).expect("Chave do gênesis em hex válido");
+        let coinbase = Transaction::coinbase_with_extra(
+            &Address::from_pubkey(&reward_pubkey, self.network),
+            self.emission.subsidy_at(0),
+            0,
+            genesis.message.as_bytes().to_vec(),
+        );
+        Block::genesis(coinbase, genesis.timestamp, genesis.difficulty, genesis.nonce)
+    }
+
+    /// Hash fixado do gênesis, usado no handshake e ao carregar arquivos
+    pub fn genesis_hash(&self) -> Hash {
+        Hash::from_hex(self.genesis.hash).expect("Hash do gênesis em hex válido")
+    }
+}
@@ -1,15 +1,17 @@
 use std::fs::File;
 use std::io::{Write, Read};
 use std::collections::HashMap;
-use crate::models::blockchain::{Blockchain, BlockchainSnapshot, UTXOKey};
-use crate::models::transaction::Output;
+use crate::models::blockchain::{Blockchain, BlockchainSnapshot, UTXOKey, UtxoEntry};
+use crate::models::chain_params::ChainParams;
 
 impl Blockchain {
     pub fn save_to_file(&self, path: &str) -> std::io::Result<()> {
         let snapshot = BlockchainSnapshot {
             chain: self.chain.clone(),
             utxos: self.utxos.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
-            mempool: self.mempool.clone(),
+            mempool: self.mempool.transactions(),
+            side_blocks: self.side_blocks(),
+            undo: self.undo.clone(),
         };
 
         let json = serde_json::to_string_pretty(&snapshot).expect("Erro ao serializar");
@@ -20,18 +22,45 @@ impl Blockchain {
         Ok(())
     }
 
-    pub fn load_from_file(path: &str) -> std::io::Result<Self> {
+    pub fn load_from_file(path: &str, params: ChainParams) -> std::io::Result<Self> {
         let mut file = File::open(path)?;
         let mut json = String::new();
         file.read_to_string(&mut json)?;
 
         let snapshot: BlockchainSnapshot = serde_json::from_str(&json).expect("Erro ao ler JSON");
-        let utxos_map: HashMap<UTXOKey, Output> = snapshot.utxos.into_iter().collect();
 
-        Ok(Blockchain {
-            chain: snapshot.chain,
-            utxos: utxos_map,
-            mempool: snapshot.mempool,
-        })
+        // Um arquivo de outra rede (ou de antes do gênesis fixo) não pode ser usado
+        let expected = params.genesis_hash();
+        let found = snapshot.chain.first().map(|b| b.header.calculate_hash());
+        if found != Some(expected) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Gênesis do arquivo ({:?}) não é o da rede ({})", found, expected),
+            ));
+        }
+        let utxos_map: HashMap<UTXOKey, UtxoEntry> = snapshot.utxos.into_iter().collect();
+
+        let mut bc = Blockchain::empty(params);
+        bc.chain = snapshot.chain;
+        bc.utxos = utxos_map;
+        bc.undo = snapshot.undo;
+
+        // Arquivos sem undo completo: recalcula reaplicando a corrente
+        if bc.undo.len() != bc.chain.len() {
+            bc.rebuild_utxos();
+        }
+
+        // Reconstrói a árvore de blocos: primeiro o branch ativo, depois os laterais
+        for block in bc.chain.clone().into_iter().chain(snapshot.side_blocks) {
+            bc.index_block(block);
+        }
+
+        // A mempool passa de novo pela validação; o que não vale mais fica de fora.
+        // Cada transação mantém o horário de entrada, então a expiração continua contando
+        for (tx, time) in snapshot.mempool {
+            let _ = bc.submit_transaction_at(tx, time);
+        }
+        bc.expire_mempool();
+        Ok(bc)
     }
 }
\ No newline at end of file
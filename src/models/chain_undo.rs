@@ -0,0 +1,60 @@
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use crate::models::blockchain::{Blockchain, UTXOKey, UtxoEntry};
+use crate::models::block::Block;
+
+/// Tudo que um bloco mudou no UTXO set, para poder desconectá-lo depois
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BlockUndo {
+    pub spent: Vec<(UTXOKey, UtxoEntry)>,   // Outputs consumidos pelos inputs do bloco
+    pub created: Vec<UTXOKey>,              // Outputs criados pelas transações do bloco
+}
+
+impl Blockchain {
+
+    /// Remove o bloco do topo e devolve as transações dele para a mempool
+    ///
+    /// O que deixou de valer sem o bloco (ex.: gastos da coinbase dele) sai
+    /// da mempool. Retorna o bloco desconectado (ele continua na árvore de blocos)
+    pub fn disconnect_tip(&mut self) -> Option<Block> {
+        let block = self.undo_tip()?;
+        self.resubmit_disconnected(std::slice::from_ref(&block), &HashSet::new());
+        self.remove_invalid_from_mempool();
+        Some(block)
+    }
+
+    /// Remove o bloco do topo e restaura o UTXO set exatamente como era antes dele
+    ///
+    /// Não mexe na mempool: quem chama decide o que fazer com as transações
+    pub(crate) fn undo_tip(&mut self) -> Option<Block> {
+        let block = self.chain.pop()?;
+        let undo = self.undo.pop()?;
+
+        for key in &undo.created {
+            self.utxos.remove(key);
+        }
+
+        // Um output criado e gasto dentro do próprio bloco não existia antes dele
+        let created: HashSet<&UTXOKey> = undo.created.iter().collect();
+        for (key, entry) in undo.spent {
+            if !created.contains(&key) {
+                self.utxos.insert(key, entry);
+            }
+        }
+        Some(block)
+    }
+
+    /// Reaplica o branch ativo desde o gênesis, recriando UTXO set e undo
+    ///
+    /// Usado ao carregar arquivos antigos que não tinham os registros de undo
+    pub(crate) fn rebuild_utxos(&mut self) {
+        self.utxos.clear();
+        self.undo.clear();
+        let chain = std::mem::take(&mut self.chain);
+        for (height, block) in chain.iter().enumerate() {
+            let undo = self.apply_block_utxos(block, height as u64);
+            self.undo.push(undo);
+        }
+        self.chain = chain;
+    }
+}
@@ -1,55 +1,266 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use chrono::Utc;
 use ed25519_dalek::VerifyingKey;
-use crate::models::blockchain::{Blockchain, UTXOKey, MINING_REWARD};
-use crate::models::block::Block;
-use crate::models::transaction::Transaction;
+use crate::crypto::hashing::Hash;
+use crate::models::blockchain::{Blockchain, UTXOKey};
+use crate::models::block::{Block, BlockHeader};
+use crate::models::errors::{BlockValidationError, TxValidationError};
+use crate::models::transaction::{Transaction, LOCKTIME_THRESHOLD, MAX_COINBASE_EXTRA};
+use crate::models::utxo_view::UtxoView;
+
+/// Fonte do horário do nó (segundos Unix), substituível em testes
+pub type Clock = Arc<dyn Fn() -> i64 + Send + Sync>;
+
+pub fn system_clock() -> Clock {
+    Arc::new(|| Utc::now().timestamp())
+}
+
+/// Regras de consenso para o timestamp dos headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampRules {
+    pub median_window: usize,           // Quantos blocos entram no median-time-past
+    pub max_future_drift: i64,          // Quanto um bloco pode estar à frente do relógio (segundos)
+}
 
 impl Blockchain {
-    pub fn validate_transaction(&self, tx: &Transaction) -> Result<u64, String> {
+
+    /// Mediana dos timestamps dos últimos blocos do branch ativo
+    ///
+    /// Usar a mediana em vez do último bloco impede que um único minerador
+    /// com relógio errado arraste o tempo da corrente
+    pub fn median_time_past(&self) -> i64 {
+        self.median_time_past_after(&self.tip_hash()).unwrap_or(0)
+    }
+
+    /// Median-time-past de `parent` e seus ancestrais, em qualquer branch
+    ///
+    /// Retorna `None` se o pai não estiver na árvore
+    pub fn median_time_past_after(&self, parent: &Hash) -> Option<i64> {
+        let mut times: Vec<i64> = self.ancestor_headers(parent, self.params.timestamp_rules.median_window)
+            .iter()
+            .map(|header| header.timestamp)
+            .collect();
+        if times.is_empty() { return None; }
+        times.sort_unstable();
+        Some(times[times.len() / 2])
+    }
+
+    /// Valida o timestamp de um header que será conectado sobre o topo atual
+    pub fn validate_timestamp(&self, header: &BlockHeader) -> Result<(), BlockValidationError> {
+        self.validate_timestamp_after(header, &self.tip_hash())
+    }
+
+    /// Valida o timestamp de um header que será conectado sobre `parent`
+    pub fn validate_timestamp_after(&self, header: &BlockHeader, parent: &Hash) -> Result<(), BlockValidationError> {
+        if let Some(median_time_past) = self.median_time_past_after(parent)
+            && header.timestamp <= median_time_past
+        {
+            return Err(BlockValidationError::TimestampTooOld { timestamp: header.timestamp, median_time_past });
+        }
+
+        let limit = (self.clock)() + self.params.timestamp_rules.max_future_drift;
+        if header.timestamp > limit {
+            return Err(BlockValidationError::TimestampTooFarInFuture { timestamp: header.timestamp, limit });
+        }
+        Ok(())
+    }
+
+    /// Valida uma transação contra o UTXO set e retorna a taxa paga
+    pub fn validate_transaction(&self, tx: &Transaction) -> Result<u64, TxValidationError> {
+        self.validate_transaction_in(tx, &UtxoView::new(&self.utxos, self.chain.len() as u64))
+    }
+
+    /// Valida uma transação contra uma visão do UTXO set (ex.: o meio de um bloco)
+    pub fn validate_transaction_in(&self, tx: &Transaction, view: &UtxoView) -> Result<u64, TxValidationError> {
+        let size = tx.serialized_size();
+        if size > self.params.max_tx_size {
+            return Err(TxValidationError::TransactionTooLarge { size, max: self.params.max_tx_size });
+        }
+        self.validate_multisig_outputs(tx)?;
+        self.validate_htlc_outputs(tx)?;
         if tx.is_coinbase() { return Ok(0); }
+        if tx.inputs.is_empty() { return Err(TxValidationError::NoInputs); }
 
-        let mut input_value = 0;
-        for input in &tx.inputs {
-            let key = UTXOKey { tx_hash: input.prev_tx_hash, output_index: input.output_index };
+        // Travas são checadas contra o bloco que incluiria a transação
+        if !tx.is_final(view.height, self.median_time_past()) {
+            return Err(TxValidationError::LockTimeNotReached { lock_time: tx.lock_time });
+        }
 
-            if let Some(utxo) = self.utxos.get(&key) {
-                input_value += utxo.value;
-                let pubkey_bytes = hex::decode(&utxo.pubkey).map_err(|_| "Hex inválido")?;
-                let public_key = VerifyingKey::from_bytes(&pubkey_bytes.try_into().map_err(|_| "Tamanho chave inválido")?).map_err(|_| "Erro chave")?;
+        let mut seen = HashSet::new();
+        let mut input_value: u64 = 0;
+        for (input_index, input) in tx.inputs.iter().enumerate() {
+            let key = UTXOKey { tx_hash: input.prev_tx_hash, output_index: input.output_index };
+            // A mesma transação não pode gastar o mesmo output duas vezes
+            if !seen.insert(key.clone()) {
+                return Err(TxValidationError::DuplicateInput { input_index });
+            }
 
-                if !tx.verify(&public_key) {
-                    return Err("Assinatura inválida!".to_string());
+            if let Some(entry) = view.get(&key) {
+                // Recompensas de mineração precisam de `coinbase_maturity` confirmações
+                let confirmations = view.height.saturating_sub(entry.height);
+                if entry.is_coinbase && confirmations < self.params.coinbase_maturity {
+                    return Err(TxValidationError::ImmatureCoinbase {
+                        input_index,
+                        confirmations,
+                        required: self.params.coinbase_maturity,
+                    });
+                }
+                // Trava relativa: idade mínima do output gasto, em blocos
+                if let Some(required) = input.relative_lock()
+                    && confirmations < required
+                {
+                    return Err(TxValidationError::RelativeLockNotReached { input_index, required, confirmations });
                 }
+
+                input_value = input_value.checked_add(entry.output.value)
+                    .ok_or(TxValidationError::ValueOverflow)?;
+                // Cada input precisa satisfazer o script do output que ele gasta
+                tx.verify_input(input_index, entry, view.height, &self.params.script_limits)
+                    .map_err(|error| TxValidationError::InvalidScript { input_index, error })?;
             } else {
-                return Err("Input inexistente ou gasto!".to_string());
+                return Err(TxValidationError::MissingInput { input_index, outpoint: key });
             }
         }
 
-        let output_value: u64 = tx.outputs.iter().map(|o| o.value).sum();
+        // Uma soma que estoura criaria moedas do nada em builds de release
+        let output_value = tx.outputs.iter()
+            .try_fold(0u64, |acc, o| acc.checked_add(o.value))
+            .ok_or(TxValidationError::ValueOverflow)?;
         if input_value < output_value {
-            return Err("Saldo insuficiente!".to_string());
+            return Err(TxValidationError::InsufficientFunds { input_value, output_value });
         }
         Ok(input_value - output_value)
     }
 
-    pub fn validate_block(&self, block: &Block) -> bool {
+    /// Outputs multisig precisam poder ser gastos um dia
+    ///
+    /// Exige 1 <= M <= N <= `max_multisig_keys` e N chaves ed25519 válidas e distintas
+    pub fn validate_multisig_outputs(&self, tx: &Transaction) -> Result<(), TxValidationError> {
+        for (output_index, output) in tx.outputs.iter().enumerate() {
+            let Some((threshold, pubkeys)) = output.script_pubkey.as_multisig() else { continue; };
+
+            let distinct: HashSet<&Vec<u8>> = pubkeys.iter().collect();
+            let keys_valid = pubkeys.iter().all(|key| is_valid_pubkey(key));
+            if threshold == 0
+                || threshold > pubkeys.len()
+                || pubkeys.len() > self.params.script_limits.max_multisig_keys
+                || distinct.len() != pubkeys.len()
+                || !keys_valid
+            {
+                return Err(TxValidationError::InvalidMultisig { output_index });
+            }
+        }
+        Ok(())
+    }
+
+    /// Outputs HTLC precisam ter os dois caminhos gastáveis
+    ///
+    /// Exige chaves ed25519 válidas e um prazo em altura de bloco (não nulo e
+    /// abaixo de `LOCKTIME_THRESHOLD`), já que o reembolso é contado em blocos
+    pub fn validate_htlc_outputs(&self, tx: &Transaction) -> Result<(), TxValidationError> {
+        for (output_index, output) in tx.outputs.iter().enumerate() {
+            let Some(htlc) = output.script_pubkey.as_htlc() else { continue; };
+
+            let keys_valid = [htlc.payee, htlc.refund].iter().all(|key| is_valid_pubkey(key));
+            if !keys_valid || htlc.timeout == 0 || htlc.timeout >= LOCKTIME_THRESHOLD {
+                return Err(TxValidationError::InvalidHtlc { output_index });
+            }
+        }
+        Ok(())
+    }
+
+    /// Limites baratos de checar, aplicados antes de qualquer outra validação
+    pub fn validate_block_limits(&self, block: &Block) -> Result<(), BlockValidationError> {
+        let count = block.transactions.len();
+        if count > self.params.max_block_transactions {
+            return Err(BlockValidationError::TooManyTransactions { count, max: self.params.max_block_transactions });
+        }
+        let size = block.serialized_size();
+        if size > self.params.max_block_size {
+            return Err(BlockValidationError::BlockTooLarge { size, max: self.params.max_block_size });
+        }
+        Ok(())
+    }
+
+    /// Valida um bloco que será conectado sobre o topo atual
+    pub fn validate_block(&self, block: &Block) -> Result<(), BlockValidationError> {
+        self.validate_block_limits(block)?;
+
         if let Some(last_block) = self.chain.last() {
-            if block.header.prev_block_hash != last_block.header.calculate_hash() { return false; }
+            let expected = last_block.header.calculate_hash();
+            if block.header.prev_block_hash != expected {
+                return Err(BlockValidationError::PrevHashMismatch { expected, found: block.header.prev_block_hash });
+            }
+            // A dificuldade declarada tem que ser a calculada pelo retarget
+            let expected = self.next_difficulty();
+            if block.header.difficulty != expected {
+                return Err(BlockValidationError::WrongDifficulty { expected, found: block.header.difficulty });
+            }
         }
 
-        if block.header.calculate_hash().count_leading_zeros() < block.header.difficulty { return false; }
-        if !self.validate_mining_reward(block) { return false; }
+        self.validate_timestamp(&block.header)?;
 
-        block.transactions.iter().all(|tx| self.validate_transaction(tx).is_ok())
+        let hash = block.header.calculate_hash();
+        if hash.count_leading_zeros() < block.header.difficulty {
+            return Err(BlockValidationError::InsufficientProofOfWork { hash, difficulty: block.header.difficulty });
+        }
+        self.validate_coinbase(block)?;
+
+        // Aplica as transações em sequência: um output gasto por uma transação
+        // deixa de existir para as seguintes, e um criado já pode ser gasto
+        let mut view = UtxoView::new(&self.utxos, self.chain.len() as u64);
+        let mut total_fees = 0;
+        for tx in &block.transactions {
+            total_fees += self.validate_transaction_in(tx, &view).map_err(|error| {
+                BlockValidationError::InvalidTransaction { tx_hash: tx.calculate_hash(), error }
+            })?;
+            view.apply(tx);
+        }
+
+        self.validate_mining_reward(block, total_fees)
     }
 
-    pub fn validate_mining_reward(&self, block: &Block) -> bool {
-        if let Some(cb_tx) = block.transactions.iter().find(|tx| tx.is_coinbase()) {
-            let total_fees: u64 = block.transactions.iter()
-                .filter(|tx| !tx.is_coinbase())
-                .map(|tx| self.validate_transaction(tx).unwrap_or(0))
-                .sum();
+    /// A coinbase tem que ser a primeira (e única) e comprometer a altura do bloco
+    pub fn validate_coinbase(&self, block: &Block) -> Result<(), BlockValidationError> {
+        let cb_tx = block.transactions.first()
+            .filter(|tx| tx.is_coinbase())
+            .ok_or(BlockValidationError::MissingCoinbase)?;
+
+        if let Some(index) = block.transactions.iter().skip(1).position(|tx| tx.is_coinbase()) {
+            return Err(BlockValidationError::MultipleCoinbase { index: index + 1 });
+        }
+
+        let expected = self.chain.len() as u64;
+        let found = cb_tx.coinbase_height();
+        if found != Some(expected) {
+            return Err(BlockValidationError::WrongCoinbaseHeight { expected, found });
+        }
 
-            cb_tx.outputs[0].value == MINING_REWARD + total_fees
-        } else { false }
+        let extra_size = cb_tx.inputs[0].coinbase.as_ref().map_or(0, |data| data.extra.len());
+        if extra_size > MAX_COINBASE_EXTRA {
+            return Err(BlockValidationError::CoinbaseExtraTooLarge { size: extra_size });
+        }
+        Ok(())
+    }
+
+    pub fn validate_mining_reward(&self, block: &Block, total_fees: u64) -> Result<(), BlockValidationError> {
+        let cb_tx = block.transactions.first()
+            .filter(|tx| tx.is_coinbase())
+            .ok_or(BlockValidationError::MissingCoinbase)?;
+
+        // O minerador pode pedir menos que o permitido, nunca mais
+        let max = self.subsidy_at(self.chain.len() as u64) + total_fees;
+        let found = cb_tx.outputs.iter()
+            .try_fold(0u64, |acc, o| acc.checked_add(o.value))
+            .unwrap_or(u64::MAX);
+        if found > max {
+            return Err(BlockValidationError::ExcessiveCoinbaseReward { max, found });
+        }
+        Ok(())
     }
-}
\ No newline at end of file
+}
+
+fn is_valid_pubkey(key: &[u8]) -> bool {
+    <[u8; 32]>::try_from(key).is_ok_and(|bytes| VerifyingKey::from_bytes(&bytes).is_ok())
+}
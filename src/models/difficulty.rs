@@ -0,0 +1,71 @@
+use crate::models::blockchain::Blockchain;
+use crate::crypto::hashing::Hash;
+
+/// Configuração do ajuste automático de dificuldade
+///
+/// A dificuldade é medida em bits zero, então cada passo de ajuste
+/// dobra (ou divide por dois) o trabalho esperado por bloco
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetargetParams {
+    pub window: usize,                  // Blocos entre dois ajustes
+    pub target_block_time: i64,         // Intervalo desejado entre blocos (segundos)
+    pub max_adjustment: usize,          // Máximo de bits alterados por ajuste
+    pub min_difficulty: usize,
+    pub max_difficulty: usize,
+}
+
+impl RetargetParams {
+    /// Calcula a nova dificuldade a partir do tempo gasto na última janela
+    ///
+    /// Blocos rápidos demais aumentam a dificuldade, lentos demais diminuem,
+    /// um bit por vez enquanto o tempo real estiver fora do dobro/metade do esperado
+    pub fn retarget(&self, current: usize, actual_timespan: i64) -> usize {
+        let expected = self.target_block_time * (self.window as i64 - 1);
+        // Evita divisão por zero e timestamps fora de ordem
+        let actual = actual_timespan.max(1);
+
+        let mut next = current;
+        let mut scaled = actual;
+        for _ in 0..self.max_adjustment {
+            if scaled * 2 > expected { break; }
+            scaled *= 2;
+            next += 1;
+        }
+        let mut scaled = actual;
+        for _ in 0..self.max_adjustment {
+            if scaled < expected * 2 || next == 0 { break; }
+            scaled /= 2;
+            next -= 1;
+        }
+
+        next.clamp(self.min_difficulty, self.max_difficulty)
+    }
+}
+
+impl Blockchain {
+
+    /// Dificuldade exigida para o próximo bloco sobre o topo atual
+    pub fn next_difficulty(&self) -> usize {
+        self.difficulty_after(&self.tip_hash()).unwrap_or(self.params.genesis.difficulty)
+    }
+
+    /// Dificuldade exigida para um bloco sobre `parent`, em qualquer branch
+    ///
+    /// Retorna `None` se o pai não estiver na árvore
+    pub fn difficulty_after(&self, parent: &Hash) -> Option<usize> {
+        let params = &self.params.retarget;
+        let entry = self.block_index.get(parent)?;
+        let tip = &entry.block.header;
+
+        let height = entry.height as usize + 1;
+        if params.window < 2 || height < params.window || !height.is_multiple_of(params.window) {
+            return Some(tip.difficulty);
+        }
+
+        // A janela vai do pai até `window - 1` blocos antes dele
+        let window = self.ancestor_headers(parent, params.window);
+        let first = window.last()?;
+        let timespan = tip.timestamp - first.timestamp;
+        Some(params.retarget(tip.difficulty, timespan))
+    }
+}
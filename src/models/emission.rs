@@ -0,0 +1,75 @@
+use crate::models::blockchain::Blockchain;
+
+/// Cronograma de emissão das recompensas de bloco
+///
+/// A recompensa começa em `initial_subsidy` e cai pela metade a cada
+/// `halving_interval` blocos. Com `tail_emission > 0` ela nunca fica abaixo
+/// desse valor (emissão perpétua); com zero o suprimento total é limitado
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmissionSchedule {
+    pub initial_subsidy: u64,
+    pub halving_interval: u64,
+    pub tail_emission: u64,
+}
+
+impl EmissionSchedule {
+    /// Recompensa (sem taxas) de um bloco na altura `height`
+    pub fn subsidy_at(&self, height: u64) -> u64 {
+        let halvings = height.checked_div(self.halving_interval).unwrap_or(0);
+        let halved = if halvings >= 64 { 0 } else { self.initial_subsidy >> halvings };
+        halved.max(self.tail_emission)
+    }
+
+    /// Total emitido pelas coinbases do gênesis até `height` (inclusive)
+    pub fn issued_until(&self, height: u64) -> u64 {
+        let blocks = height.saturating_add(1);
+        if self.halving_interval == 0 {
+            return blocks.saturating_mul(self.initial_subsidy.max(self.tail_emission));
+        }
+
+        // Soma era por era, cada uma com recompensa constante
+        let mut total: u64 = 0;
+        let mut start = 0;
+        while start < blocks {
+            let subsidy = self.subsidy_at(start);
+            let era_end = start.saturating_add(self.halving_interval).min(blocks);
+            if subsidy == self.tail_emission {
+                // Daqui em diante a recompensa não muda mais
+                return total.saturating_add((blocks - start).saturating_mul(subsidy));
+            }
+            total = total.saturating_add((era_end - start).saturating_mul(subsidy));
+            start = era_end;
+        }
+        total
+    }
+
+    /// Suprimento máximo, ou `None` se há emissão perpétua
+    pub fn max_supply(&self) -> Option<u64> {
+        if self.tail_emission > 0 { return None; }
+        let last_height = self.halving_interval.saturating_mul(64);
+        Some(self.issued_until(last_height))
+    }
+}
+
+impl Blockchain {
+
+    pub fn subsidy_at(&self, height: u64) -> u64 {
+        self.params.emission.subsidy_at(height)
+    }
+
+    /// Soma de todos os outputs não gastos
+    ///
+    /// `None` se a soma passa de u64, o que por si só já denuncia inflação
+    pub fn circulating_supply(&self) -> Option<u64> {
+        self.utxos.values().try_fold(0u64, |acc, entry| acc.checked_add(entry.output.value))
+    }
+
+    /// Auditoria: o que circula não pode passar do que o cronograma emitiu
+    ///
+    /// Pode ficar abaixo (coinbases que não pediram a recompensa inteira)
+    pub fn supply_is_consistent(&self) -> bool {
+        let Some(tip_height) = self.chain.len().checked_sub(1) else { return self.utxos.is_empty(); };
+        let issued = self.params.emission.issued_until(tip_height as u64);
+        self.circulating_supply().is_some_and(|supply| supply <= issued)
+    }
+}
@@ -0,0 +1,262 @@
+use std::fmt;
+use crate::crypto::hashing::Hash;
+use crate::models::blockchain::UTXOKey;
+
+/// Motivos pelos quais um endereço digitado não é aceito
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressError {
+    InvalidCharacter { character: char, position: usize },     // Fora do alfabeto Base58
+    InvalidLength { length: usize },                            // Bytes decodificados (versão + hash + checksum)
+    InvalidChecksum,                                            // Erro de digitação ou endereço corrompido
+    WrongNetwork { expected: u8, found: u8 },                  // Byte de versão de outra rede
+}
+
+/// Motivos pelos quais a avaliação de um script falha
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptError {
+    MissingInput,                                               // O contexto aponta para um input inexistente
+    ScriptTooLarge { size: usize, max: usize },
+    NotPushOnly,                                                // Desbloqueio com instruções além de push
+    TooManyOps { max: usize },
+    StackOverflow { max: usize },
+    ElementTooLarge { size: usize, max: usize },
+    StackUnderflow,
+    InvalidNumber,
+    VerifyFailed,
+    EqualVerifyFailed,
+    CheckSigVerifyFailed,
+    InvalidKeyCount { count: i64, max: usize },
+    InvalidSignatureCount { count: i64, keys: usize },
+    NegativeLockTime,
+    LockTimeTypeMismatch { required: u64, lock_time: u64 },     // Um é altura e o outro timestamp
+    LockTimeNotReached { required: u64, lock_time: u64 },       // lock_time da transação abaixo do exigido
+    FinalSequence,                                              // Input com SEQUENCE_FINAL desliga o lock_time
+    SequenceNotReached { required: u64, confirmations: u64 },
+    UnbalancedConditional,                                      // ELSE/ENDIF sem IF, ou IF sem ENDIF
+    EvalFalse,                                                  // Terminou com falso (ou nada) no topo
+    CleanStack { size: usize },                                 // Sobrou mais que o resultado na pilha
+}
+
+/// Motivos pelos quais uma transação é rejeitada
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxValidationError {
+    NoInputs,
+    TransactionTooLarge { size: usize, max: usize },
+    UnexpectedCoinbase,                                         // Coinbase fora de um bloco
+    MissingInput { input_index: usize, outpoint: UTXOKey },   // Inexistente ou já gasto
+    DuplicateInput { input_index: usize },
+    ImmatureCoinbase { input_index: usize, confirmations: u64, required: u64 },
+    InvalidScript { input_index: usize, error: ScriptError },
+    InvalidMultisig { output_index: usize },                   // Limiar ou chaves que tornariam o output ingastável
+    InvalidHtlc { output_index: usize },                       // Chave ou prazo que tornariam um dos caminhos ingastável
+    LockTimeNotReached { lock_time: u64 },                     // Altura ou timestamp ainda não alcançado
+    RelativeLockNotReached { input_index: usize, required: u64, confirmations: u64 },
+    InsufficientFunds { input_value: u64, output_value: u64 },
+    ValueOverflow,                                              // Soma das entradas ou das saídas passa de u64
+    AlreadyInMempool { tx_hash: Hash },
+    MempoolConflict { input_index: usize, conflicting_tx: Hash },  // Output já gasto por outra da mempool
+    FeeTooLow { fee: u64, required: u64 },                    // Abaixo da taxa mínima de relay
+    MempoolFull { min_fee_rate_per_kb: u64 },                  // Não paga o bastante para tomar a vaga de outra
+    ReplacementFeeRateTooLow { conflicting_tx: Hash },         // RBF precisa pagar mais por byte
+    InsufficientReplacementFee { fee: u64, required: u64 },    // RBF precisa cobrir as taxas despejadas
+    TooManyReplacements { count: usize, max: usize },
+    ReplacementSpendsConflict { input_index: usize },           // RBF gastando output de quem ela despeja
+    TooManyAncestors { count: usize, max: usize },             // Cadeia de não confirmadas longa demais
+    TooManyDescendants { tx_hash: Hash, count: usize, max: usize },
+}
+
+/// Motivos pelos quais um bloco é rejeitado
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockValidationError {
+    UnknownParent { prev_block_hash: Hash },                   // Bloco órfão
+    UnexpectedGenesis { hash: Hash },                           // Sem pai, mas não é o gênesis da rede
+    BlockTooLarge { size: usize, max: usize },
+    TooManyTransactions { count: usize, max: usize },
+    PrevHashMismatch { expected: Hash, found: Hash },
+    WrongDifficulty { expected: usize, found: usize },
+    InsufficientProofOfWork { hash: Hash, difficulty: usize },
+    TimestampTooOld { timestamp: i64, median_time_past: i64 },
+    TimestampTooFarInFuture { timestamp: i64, limit: i64 },
+    MissingCoinbase,                                            // Ausente ou fora da primeira posição
+    MultipleCoinbase { index: usize },
+    WrongCoinbaseHeight { expected: u64, found: Option<u64> },
+    CoinbaseExtraTooLarge { size: usize },
+    ExcessiveCoinbaseReward { max: u64, found: u64 },          // Soma dos outputs da coinbase
+    InvalidTransaction { tx_hash: Hash, error: TxValidationError },
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressError::InvalidCharacter { character, position } =>
+                write!(f, "Caractere '{}' inválido na posição {} do endereço", character, position),
+            AddressError::InvalidLength { length } =>
+                write!(f, "Endereço decodifica para {} bytes", length),
+            AddressError::InvalidChecksum =>
+                write!(f, "Checksum do endereço não confere"),
+            AddressError::WrongNetwork { expected, found } =>
+                write!(f, "Endereço com versão {:#04x} de outra rede (esperada {:#04x})", found, expected),
+        }
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::MissingInput =>
+                write!(f, "Input inexistente"),
+            ScriptError::ScriptTooLarge { size, max } =>
+                write!(f, "Script com {} bytes excede o limite de {}", size, max),
+            ScriptError::NotPushOnly =>
+                write!(f, "Script de desbloqueio só pode empilhar dados"),
+            ScriptError::TooManyOps { max } =>
+                write!(f, "Mais de {} instruções executadas", max),
+            ScriptError::StackOverflow { max } =>
+                write!(f, "Pilha passou de {} elementos", max),
+            ScriptError::ElementTooLarge { size, max } =>
+                write!(f, "Elemento com {} bytes excede o limite de {}", size, max),
+            ScriptError::StackUnderflow =>
+                write!(f, "Pilha vazia"),
+            ScriptError::InvalidNumber =>
+                write!(f, "Número com mais de 8 bytes"),
+            ScriptError::VerifyFailed =>
+                write!(f, "VERIFY encontrou falso"),
+            ScriptError::EqualVerifyFailed =>
+                write!(f, "EQUALVERIFY com valores diferentes"),
+            ScriptError::CheckSigVerifyFailed =>
+                write!(f, "Assinatura inválida"),
+            ScriptError::InvalidKeyCount { count, max } =>
+                write!(f, "Multisig com {} chaves (máximo {})", count, max),
+            ScriptError::InvalidSignatureCount { count, keys } =>
+                write!(f, "Multisig pedindo {} assinaturas de {} chaves", count, keys),
+            ScriptError::NegativeLockTime =>
+                write!(f, "Timelock negativo"),
+            ScriptError::LockTimeTypeMismatch { required, lock_time } =>
+                write!(f, "Timelock {} e lock_time {} não são do mesmo tipo (altura ou timestamp)", required, lock_time),
+            ScriptError::LockTimeNotReached { required, lock_time } =>
+                write!(f, "Exige lock_time de pelo menos {} (a transação tem {})", required, lock_time),
+            ScriptError::FinalSequence =>
+                write!(f, "CHECKLOCKTIMEVERIFY num input com sequence final"),
+            ScriptError::SequenceNotReached { required, confirmations } =>
+                write!(f, "Precisa de {} confirmações do output gasto (tem {})", required, confirmations),
+            ScriptError::UnbalancedConditional =>
+                write!(f, "IF/ELSE/ENDIF desbalanceados"),
+            ScriptError::EvalFalse =>
+                write!(f, "Script terminou com falso"),
+            ScriptError::CleanStack { size } =>
+                write!(f, "Script terminou com {} itens na pilha em vez de 1", size),
+        }
+    }
+}
+
+impl fmt::Display for TxValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxValidationError::NoInputs =>
+                write!(f, "Transação sem inputs"),
+            TxValidationError::TransactionTooLarge { size, max } =>
+                write!(f, "Transação com {} bytes excede o limite de {}", size, max),
+            TxValidationError::UnexpectedCoinbase =>
+                write!(f, "Coinbase só pode ser criada pelo minerador do bloco"),
+            TxValidationError::MissingInput { input_index, outpoint } =>
+                write!(f, "Input {} inexistente ou gasto ({}:{})", input_index, outpoint.tx_hash, outpoint.output_index),
+            TxValidationError::DuplicateInput { input_index } =>
+                write!(f, "Input {} gasta um output já usado pela mesma transação", input_index),
+            TxValidationError::ImmatureCoinbase { input_index, confirmations, required } =>
+                write!(f, "Input {} gasta uma coinbase com {} de {} confirmações necessárias", input_index, confirmations, required),
+            TxValidationError::InvalidScript { input_index, error } =>
+                write!(f, "Script do input {} falhou: {}", input_index, error),
+            TxValidationError::InvalidMultisig { output_index } =>
+                write!(f, "Output {} multisig com limiar ou chaves inválidas", output_index),
+            TxValidationError::InvalidHtlc { output_index } =>
+                write!(f, "Output {} HTLC com chave ou prazo inválidos", output_index),
+            TxValidationError::LockTimeNotReached { lock_time } =>
+                write!(f, "Transação travada até {}", lock_time),
+            TxValidationError::RelativeLockNotReached { input_index, required, confirmations } =>
+                write!(f, "Input {} exige {} confirmações do output gasto (tem {})", input_index, required, confirmations),
+            TxValidationError::InsufficientFunds { input_value, output_value } =>
+                write!(f, "Saldo insuficiente: entradas {} < saídas {}", input_value, output_value),
+            TxValidationError::ValueOverflow =>
+                write!(f, "Soma dos valores da transação estoura o limite"),
+            TxValidationError::AlreadyInMempool { tx_hash } =>
+                write!(f, "Transação {} já está na mempool", tx_hash),
+            TxValidationError::MempoolConflict { input_index, conflicting_tx } =>
+                write!(f, "Input {} já é gasto pela transação {} da mempool", input_index, conflicting_tx),
+            TxValidationError::FeeTooLow { fee, required } =>
+                write!(f, "Taxa {} abaixo do mínimo de relay {}", fee, required),
+            TxValidationError::MempoolFull { min_fee_rate_per_kb } =>
+                write!(f, "Mempool cheia: é preciso pagar mais de {} por 1000 bytes", min_fee_rate_per_kb),
+            TxValidationError::ReplacementFeeRateTooLow { conflicting_tx } =>
+                write!(f, "Substituta não paga mais por byte que a transação {}", conflicting_tx),
+            TxValidationError::InsufficientReplacementFee { fee, required } =>
+                write!(f, "Taxa {} da substituta abaixo do mínimo {}", fee, required),
+            TxValidationError::TooManyReplacements { count, max } =>
+                write!(f, "Substituição despejaria {} transações (máximo {})", count, max),
+            TxValidationError::ReplacementSpendsConflict { input_index } =>
+                write!(f, "Input {} gasta um output de uma transação que seria substituída", input_index),
+            TxValidationError::TooManyAncestors { count, max } =>
+                write!(f, "Cadeia com {} transações não confirmadas (máximo {})", count, max),
+            TxValidationError::TooManyDescendants { tx_hash, count, max } =>
+                write!(f, "Transação {} ficaria com {} descendentes na mempool (máximo {})", tx_hash, count, max),
+        }
+    }
+}
+
+impl fmt::Display for BlockValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockValidationError::UnknownParent { prev_block_hash } =>
+                write!(f, "Bloco pai desconhecido: {}", prev_block_hash),
+            BlockValidationError::UnexpectedGenesis { hash } =>
+                write!(f, "Bloco {} sem pai não é o gênesis da rede", hash),
+            BlockValidationError::BlockTooLarge { size, max } =>
+                write!(f, "Bloco com {} bytes excede o limite de {}", size, max),
+            BlockValidationError::TooManyTransactions { count, max } =>
+                write!(f, "Bloco com {} transações excede o limite de {}", count, max),
+            BlockValidationError::PrevHashMismatch { expected, found } =>
+                write!(f, "Hash anterior {} não aponta para o topo {}", found, expected),
+            BlockValidationError::WrongDifficulty { expected, found } =>
+                write!(f, "Dificuldade {} diferente da esperada {}", found, expected),
+            BlockValidationError::InsufficientProofOfWork { hash, difficulty } =>
+                write!(f, "Hash {} não atinge a dificuldade {}", hash, difficulty),
+            BlockValidationError::TimestampTooOld { timestamp, median_time_past } =>
+                write!(f, "Timestamp {} não é maior que o median-time-past {}", timestamp, median_time_past),
+            BlockValidationError::TimestampTooFarInFuture { timestamp, limit } =>
+                write!(f, "Timestamp {} está no futuro (limite {})", timestamp, limit),
+            BlockValidationError::MissingCoinbase =>
+                write!(f, "Bloco sem transação coinbase na primeira posição"),
+            BlockValidationError::MultipleCoinbase { index } =>
+                write!(f, "Coinbase extra na posição {} do bloco", index),
+            BlockValidationError::WrongCoinbaseHeight { expected, found } =>
+                write!(f, "Coinbase compromete a altura {:?}, esperada {}", found, expected),
+            BlockValidationError::CoinbaseExtraTooLarge { size } =>
+                write!(f, "Dados extras da coinbase com {} bytes", size),
+            BlockValidationError::ExcessiveCoinbaseReward { max, found } =>
+                write!(f, "Coinbase paga {} mas o máximo permitido é {}", found, max),
+            BlockValidationError::InvalidTransaction { tx_hash, error } =>
+                write!(f, "Transação {} inválida: {}", tx_hash, error),
+        }
+    }
+}
+
+impl std::error::Error for AddressError {}
+
+impl std::error::Error for ScriptError {}
+
+impl std::error::Error for TxValidationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TxValidationError::InvalidScript { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for BlockValidationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BlockValidationError::InvalidTransaction { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
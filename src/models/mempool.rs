@@ -0,0 +1,454 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::models::blockchain::{Blockchain, UTXOKey};
+use crate::models::block::Block;
+use crate::models::errors::TxValidationError;
+use crate::models::transaction::Transaction;
+use crate::crypto::hashing::Hash;
+
+/// Regras locais do nó para aceitar e manter transações
+///
+/// Não são consenso: cada nó pode escolher as suas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MempoolPolicy {
+    pub max_size: usize,                // Soma dos bytes serializados das transações
+    pub expiry: i64,                    // Segundos até uma transação esquecida ser descartada
+    pub min_relay_fee_per_kb: u64,      // Taxa mínima a cada 1000 bytes
+    pub max_replaced: usize,            // Máximo de transações (com descendentes) que um RBF despeja
+    pub max_ancestors: usize,           // Tamanho máximo da cadeia acima de uma transação (contando ela)
+    pub max_descendants: usize,         // Tamanho máximo da árvore abaixo de uma transação (contando ela)
+}
+
+impl Default for MempoolPolicy {
+    fn default() -> Self {
+        MempoolPolicy {
+            max_size: 300_000_000,
+            expiry: 14 * 24 * 60 * 60,
+            min_relay_fee_per_kb: 1,
+            max_replaced: 100,
+            max_ancestors: 25,
+            max_descendants: 25,
+        }
+    }
+}
+
+impl MempoolPolicy {
+    /// Taxa mínima para uma transação de `size` bytes (arredonda para cima)
+    pub fn min_relay_fee(&self, size: usize) -> u64 {
+        (size as u64 * self.min_relay_fee_per_kb).div_ceil(1000)
+    }
+}
+
+/// Transação aceita na mempool e o que já foi calculado sobre ela
+#[derive(Debug, Clone)]
+pub struct MempoolEntry {
+    pub tx: Transaction,
+    pub fee: u64,
+    pub size: usize,                    // Bytes serializados
+    pub time: i64,                      // Quando entrou (relógio do nó)
+    pub parents: HashSet<Hash>,         // Transações da mempool cujos outputs ela gasta
+    pub children: HashSet<Hash>,        // Transações da mempool que gastam outputs dela
+    pub(crate) sequence: u64,           // Ordem de chegada
+}
+
+impl MempoolEntry {
+    /// Compara taxa por byte sem divisão: a/b < c/d  <=>  a*d < c*b
+    pub fn cmp_fee_rate(&self, other: &MempoolEntry) -> Ordering {
+        (self.fee as u128 * other.size as u128).cmp(&(other.fee as u128 * self.size as u128))
+    }
+}
+
+/// Resultado de uma transação aceita na mempool
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MempoolAcceptance {
+    pub tx_hash: Hash,
+    pub fee: u64,
+    pub replaced: Vec<Hash>,            // Substituídas por replace-by-fee (com descendentes)
+    pub evicted: Vec<Hash>,             // Despejadas pelo limite de memória
+}
+
+/// Transações aguardando um bloco
+///
+/// Indexada por txid e pelos outputs que cada transação gasta, com limite
+/// de memória (sai quem paga menos por byte) e expiração por tempo
+#[derive(Debug, Clone, Default)]
+pub struct Mempool {
+    entries: HashMap<Hash, MempoolEntry>,
+    spent: HashMap<UTXOKey, Hash>,      // Output -> transação da mempool que o gasta
+    total_size: usize,
+    next_sequence: u64,
+    pub policy: MempoolPolicy,
+}
+
+impl Mempool {
+    pub fn new(policy: MempoolPolicy) -> Self {
+        Mempool { policy, ..Default::default() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Bytes ocupados pelas transações
+    pub fn total_size(&self) -> usize {
+        self.total_size
+    }
+
+    pub fn contains(&self, tx_hash: &Hash) -> bool {
+        self.entries.contains_key(tx_hash)
+    }
+
+    pub fn get(&self, tx_hash: &Hash) -> Option<&MempoolEntry> {
+        self.entries.get(tx_hash)
+    }
+
+    /// Transação da mempool que gasta o output, se houver
+    pub fn spender(&self, outpoint: &UTXOKey) -> Option<Hash> {
+        self.spent.get(outpoint).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Hash, &MempoolEntry)> {
+        self.entries.iter()
+    }
+
+    /// Transações na ordem em que chegaram, com o horário de entrada
+    pub fn transactions(&self) -> Vec<(Transaction, i64)> {
+        let mut entries: Vec<&MempoolEntry> = self.entries.values().collect();
+        entries.sort_by_key(|entry| entry.sequence);
+        entries.into_iter().map(|entry| (entry.tx.clone(), entry.time)).collect()
+    }
+
+    /// Adiciona uma transação já validada e aplica os limites da política
+    ///
+    /// Recusa a transação se ela alongar demais uma cadeia de ancestrais ou
+    /// de descendentes, ou se for a que paga menos e não couber (`MempoolFull`);
+    /// nesse caso nada é despejado. Retorna as transações despejadas pelo limite de memória
+    pub fn insert(&mut self, tx: Transaction, fee: u64, time: i64) -> Result<Vec<Hash>, TxValidationError> {
+        let tx_hash = self.insert_entry(tx, fee, time, self.next_sequence)?;
+        self.next_sequence += 1;
+
+        let evicted = self.evict_to_size();
+        if evicted.iter().any(|(hash, _)| *hash == tx_hash) {
+            // Quem não coube foi ela: as que saíram para abrir espaço voltam
+            let others = evicted.into_iter()
+                .filter(|(hash, _)| *hash != tx_hash)
+                .map(|(_, entry)| entry)
+                .collect();
+            self.restore(others);
+            return Err(TxValidationError::MempoolFull { min_fee_rate_per_kb: self.min_fee_rate_per_kb() });
+        }
+        Ok(evicted.into_iter().map(|(hash, _)| hash).collect())
+    }
+
+    /// Indexa a transação sem aplicar o limite de memória
+    fn insert_entry(&mut self, tx: Transaction, fee: u64, time: i64, sequence: u64) -> Result<Hash, TxValidationError> {
+        let tx_hash = tx.calculate_hash();
+        if self.entries.contains_key(&tx_hash) {
+            return Err(TxValidationError::AlreadyInMempool { tx_hash });
+        }
+
+        let parents: HashSet<Hash> = tx.inputs.iter()
+            .map(|input| input.prev_tx_hash)
+            .filter(|hash| self.entries.contains_key(hash))
+            .collect();
+        self.check_chain_limits(&parents)?;
+
+        for input in &tx.inputs {
+            let key = UTXOKey { tx_hash: input.prev_tx_hash, output_index: input.output_index };
+            self.spent.insert(key, tx_hash);
+        }
+        for parent in &parents {
+            if let Some(entry) = self.entries.get_mut(parent) {
+                entry.children.insert(tx_hash);
+            }
+        }
+        // Filhos que chegaram antes (ex.: pai devolvido por um reorg)
+        let children: HashSet<Hash> = (0..tx.outputs.len())
+            .filter_map(|output_index| self.spender(&UTXOKey { tx_hash, output_index }))
+            .collect();
+        for child in &children {
+            if let Some(entry) = self.entries.get_mut(child) {
+                entry.parents.insert(tx_hash);
+            }
+        }
+        let size = tx.serialized_size();
+        self.total_size += size;
+        let entry = MempoolEntry { tx, fee, size, time, parents, children, sequence };
+        self.entries.insert(tx_hash, entry);
+        Ok(tx_hash)
+    }
+
+    /// Devolve transações removidas (ex.: substituição que não deu certo)
+    ///
+    /// Reinsere na ordem de chegada original, então pais entram antes dos
+    /// filhos, e cada uma mantém sua posição na fila de chegada
+    pub fn restore(&mut self, mut entries: Vec<MempoolEntry>) {
+        entries.sort_by_key(|entry| entry.sequence);
+        for entry in entries {
+            let _ = self.insert_entry(entry.tx, entry.fee, entry.time, entry.sequence);
+        }
+    }
+
+    /// Remove só a transação (os filhos, se houver, continuam)
+    pub fn remove(&mut self, tx_hash: &Hash) -> Option<MempoolEntry> {
+        let entry = self.entries.remove(tx_hash)?;
+        for input in &entry.tx.inputs {
+            let key = UTXOKey { tx_hash: input.prev_tx_hash, output_index: input.output_index };
+            if self.spent.get(&key) == Some(tx_hash) {
+                self.spent.remove(&key);
+            }
+        }
+        for parent in &entry.parents {
+            if let Some(parent_entry) = self.entries.get_mut(parent) {
+                parent_entry.children.remove(tx_hash);
+            }
+        }
+        for child in &entry.children {
+            if let Some(child_entry) = self.entries.get_mut(child) {
+                child_entry.parents.remove(tx_hash);
+            }
+        }
+        self.total_size -= entry.size;
+        Some(entry)
+    }
+
+    /// A transação e todas que dependem dos outputs dela, direta ou indiretamente
+    pub fn descendants(&self, tx_hash: &Hash) -> Vec<Hash> {
+        self.walk(tx_hash, |entry| &entry.children)
+    }
+
+    /// A transação e todas de que ela depende na mempool, direta ou indiretamente
+    pub fn ancestors(&self, tx_hash: &Hash) -> Vec<Hash> {
+        self.walk(tx_hash, |entry| &entry.parents)
+    }
+
+    /// Percorre o grafo pai/filho a partir de `tx_hash` em largura
+    fn walk(&self, tx_hash: &Hash, next: impl Fn(&MempoolEntry) -> &HashSet<Hash>) -> Vec<Hash> {
+        let mut found = Vec::new();
+        let mut seen = HashSet::new();
+        let mut pending = VecDeque::from([*tx_hash]);
+        while let Some(hash) = pending.pop_front() {
+            let Some(entry) = self.entries.get(&hash) else { continue; };
+            if !seen.insert(hash) { continue; }
+            pending.extend(next(entry).iter().copied());
+            found.push(hash);
+        }
+        found
+    }
+
+    /// Uma nova transação com esses pais respeita os limites de cadeia?
+    fn check_chain_limits(&self, parents: &HashSet<Hash>) -> Result<(), TxValidationError> {
+        let mut ancestors: HashSet<Hash> = HashSet::new();
+        for parent in parents {
+            ancestors.extend(self.ancestors(parent));
+        }
+
+        let count = ancestors.len() + 1;
+        if count > self.policy.max_ancestors {
+            return Err(TxValidationError::TooManyAncestors { count, max: self.policy.max_ancestors });
+        }
+        for ancestor in &ancestors {
+            let count = self.descendants(ancestor).len() + 1;
+            if count > self.policy.max_descendants {
+                return Err(TxValidationError::TooManyDescendants { tx_hash: *ancestor, count, max: self.policy.max_descendants });
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove a transação e todas que dependem dos outputs dela
+    pub fn remove_with_descendants(&mut self, tx_hash: &Hash) -> Vec<Hash> {
+        self.take_with_descendants(tx_hash).into_iter().map(|(hash, _)| hash).collect()
+    }
+
+    /// Como `remove_with_descendants`, mas devolve as entradas removidas
+    fn take_with_descendants(&mut self, tx_hash: &Hash) -> Vec<(Hash, MempoolEntry)> {
+        self.descendants(tx_hash).into_iter()
+            .filter_map(|hash| self.remove(&hash).map(|entry| (hash, entry)))
+            .collect()
+    }
+
+    /// Transações que `tx` tiraria da mempool ao entrar (replace-by-fee)
+    ///
+    /// Só substitui conflitos que fizeram opt-in (`is_replaceable`), e só se
+    /// `tx` pagar uma taxa por byte maior que cada um deles e uma taxa absoluta
+    /// maior que todos os despejados somados, mais a própria taxa de relay.
+    /// Sem conflitos, retorna uma lista vazia
+    pub fn replacement_set(&self, tx: &Transaction, fee: u64, size: usize) -> Result<Vec<Hash>, TxValidationError> {
+        let mut conflicts: Vec<Hash> = Vec::new();
+        for (input_index, input) in tx.inputs.iter().enumerate() {
+            let key = UTXOKey { tx_hash: input.prev_tx_hash, output_index: input.output_index };
+            let Some(conflicting_tx) = self.spender(&key) else { continue; };
+            if conflicts.contains(&conflicting_tx) { continue; }
+
+            let original = &self.entries[&conflicting_tx];
+            if !original.tx.is_replaceable() {
+                return Err(TxValidationError::MempoolConflict { input_index, conflicting_tx });
+            }
+            if fee as u128 * original.size as u128 <= original.fee as u128 * size as u128 {
+                return Err(TxValidationError::ReplacementFeeRateTooLow { conflicting_tx });
+            }
+            conflicts.push(conflicting_tx);
+        }
+
+        let mut replaced: Vec<Hash> = Vec::new();
+        for conflict in &conflicts {
+            for hash in self.descendants(conflict) {
+                if !replaced.contains(&hash) {
+                    replaced.push(hash);
+                }
+            }
+        }
+        // A substituta não pode depender do que ela mesma despeja
+        for (input_index, input) in tx.inputs.iter().enumerate() {
+            if replaced.contains(&input.prev_tx_hash) {
+                return Err(TxValidationError::ReplacementSpendsConflict { input_index });
+            }
+        }
+        if replaced.len() > self.policy.max_replaced {
+            return Err(TxValidationError::TooManyReplacements { count: replaced.len(), max: self.policy.max_replaced });
+        }
+
+        // Paga o que sai e ainda o próprio relay, senão a rede retransmite de graça
+        let replaced_fees: u64 = replaced.iter().map(|hash| self.entries[hash].fee).sum();
+        let required = replaced_fees + self.policy.min_relay_fee(size).max(1);
+        if !replaced.is_empty() && fee < required {
+            return Err(TxValidationError::InsufficientReplacementFee { fee, required });
+        }
+        Ok(replaced)
+    }
+
+    /// Tira as transações confirmadas pelo bloco e as que conflitam com ele
+    ///
+    /// Conflito é gastar um output que o bloco também gastou; os
+    /// descendentes do conflito saem junto. Retorna tudo que foi removido,
+    /// para poder voltar se o bloco for desconectado logo em seguida
+    pub fn remove_for_block(&mut self, block: &Block) -> Vec<MempoolEntry> {
+        let mut removed = Vec::new();
+        for tx in &block.transactions {
+            removed.extend(self.remove(&tx.calculate_hash()));
+        }
+
+        for tx in block.transactions.iter().filter(|tx| !tx.is_coinbase()) {
+            for input in &tx.inputs {
+                let key = UTXOKey { tx_hash: input.prev_tx_hash, output_index: input.output_index };
+                if let Some(conflict) = self.spender(&key) {
+                    removed.extend(self.take_with_descendants(&conflict).into_iter().map(|(_, entry)| entry));
+                }
+            }
+        }
+        removed
+    }
+
+    /// Descarta transações que estão esperando há mais de `policy.expiry`
+    pub fn expire(&mut self, now: i64) -> Vec<Hash> {
+        let expired: Vec<Hash> = self.entries.iter()
+            .filter(|(_, entry)| now - entry.time >= self.policy.expiry)
+            .map(|(hash, _)| *hash)
+            .collect();
+
+        let mut removed = Vec::new();
+        for hash in expired {
+            removed.extend(self.remove_with_descendants(&hash));
+        }
+        removed
+    }
+
+    /// Despeja as transações de menor taxa por byte até caber no limite
+    ///
+    /// No empate sai a mais recente
+    pub fn trim_to_size(&mut self) -> Vec<Hash> {
+        self.evict_to_size().into_iter().map(|(hash, _)| hash).collect()
+    }
+
+    /// Como `trim_to_size`, mas devolve as entradas para poderem voltar
+    fn evict_to_size(&mut self) -> Vec<(Hash, MempoolEntry)> {
+        let mut evicted = Vec::new();
+        while self.total_size > self.policy.max_size {
+            let lowest = self.entries.iter()
+                .min_by(|(_, a), (_, b)| a.cmp_fee_rate(b).then(b.sequence.cmp(&a.sequence)))
+                .map(|(hash, _)| *hash);
+            let Some(lowest) = lowest else { break; };
+            evicted.extend(self.take_with_descendants(&lowest));
+        }
+        evicted
+    }
+
+    /// Menor taxa (por 1000 bytes) entre as transações que ficaram
+    ///
+    /// Em u128: uma taxa perto de u64::MAX vezes 1000 estouraria
+    fn min_fee_rate_per_kb(&self) -> u64 {
+        self.entries.values()
+            .map(|entry| {
+                let rate = (entry.fee as u128 * 1000).div_ceil(entry.size.max(1) as u128);
+                u64::try_from(rate).unwrap_or(u64::MAX)
+            })
+            .min()
+            .unwrap_or(self.policy.min_relay_fee_per_kb)
+    }
+}
+
+impl Blockchain {
+
+    /// Devolve para a mempool as transações de blocos que saíram do branch ativo
+    ///
+    /// `blocks` vem do topo antigo para baixo; as transações são reenviadas do
+    /// bloco mais antigo para o mais novo, pela mesma porta de entrada de
+    /// qualquer transação. As que estão em `confirmed` (já incluídas no branch
+    /// novo) são ignoradas. Retorna quantas voltaram e quantas foram descartadas
+    pub(crate) fn resubmit_disconnected(&mut self, blocks: &[Block], confirmed: &HashSet<Hash>) -> (usize, usize) {
+        let now = (self.clock)();
+        let transactions = blocks.iter().rev()
+            .flat_map(|block| block.transactions.iter().filter(|tx| !tx.is_coinbase()))
+            .filter(|tx| !confirmed.contains(&tx.calculate_hash()))
+            .map(|tx| (tx.clone(), now))
+            .collect();
+        self.resubmit(transactions)
+    }
+
+    /// Devolve entradas que saíram da mempool por causa de um bloco que
+    /// acabou desconectado (ex.: reorg que falhou no meio)
+    ///
+    /// Voltam na ordem de chegada original e com o horário de entrada de antes
+    pub(crate) fn resubmit_purged(&mut self, mut entries: Vec<MempoolEntry>) -> (usize, usize) {
+        entries.sort_by_key(|entry| entry.sequence);
+        self.resubmit(entries.into_iter().map(|entry| (entry.tx, entry.time)).collect())
+    }
+
+    /// Revalida e reinsere cada transação, na ordem dada
+    fn resubmit(&mut self, transactions: Vec<(Transaction, i64)>) -> (usize, usize) {
+        let mut resubmitted = 0;
+        let mut dropped = 0;
+        for (tx, time) in transactions {
+            match self.submit_transaction_at(tx, time) {
+                Ok(_) => resubmitted += 1,
+                Err(_) => dropped += 1,
+            }
+        }
+        (resubmitted, dropped)
+    }
+
+    /// Remove da mempool o que deixou de ser válido sobre o topo atual
+    ///
+    /// Depois de um reorg, inputs podem ter sumido e coinbases podem ter
+    /// voltado a ser imaturas. Retorna quantas transações saíram
+    pub(crate) fn remove_invalid_from_mempool(&mut self) -> usize {
+        let invalid: Vec<Hash> = {
+            let view = self.mempool_view();
+            self.mempool.iter()
+                .filter(|(_, entry)| self.validate_transaction_in(&entry.tx, &view).is_err())
+                .map(|(tx_hash, _)| *tx_hash)
+                .collect()
+        };
+
+        let mut removed = 0;
+        for tx_hash in invalid {
+            removed += self.mempool.remove_with_descendants(&tx_hash).len();
+        }
+        removed
+    }
+}
@@ -2,4 +2,15 @@ pub mod block;
 pub mod transaction;
 pub mod blockchain;
 pub mod chain_persistence;
-pub mod chain_validation;
\ No newline at end of file
+pub mod chain_validation;
+pub mod chain_fork;
+pub mod chain_undo;
+pub mod difficulty;
+pub mod errors;
+pub mod utxo_view;
+pub mod sighash;
+pub mod emission;
+pub mod chain_params;
+pub mod block_template;
+pub mod mempool;
+pub mod script;
\ No newline at end of file
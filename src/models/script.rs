@@ -0,0 +1,405 @@
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use crate::crypto::hashing::Hash;
+use crate::models::blockchain::UtxoEntry;
+use crate::models::errors::ScriptError;
+use crate::models::transaction::{Transaction, LOCKTIME_THRESHOLD, SEQUENCE_FINAL};
+
+/// Instruções da linguagem de script
+///
+/// Os scripts rodam sobre uma pilha de vetores de bytes. Números são
+/// inteiros de até 8 bytes little-endian; um elemento é "verdadeiro"
+/// se tiver algum byte diferente de zero
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Op {
+    PushData(Vec<u8>),
+    PushInt(i64),
+    Dup,
+    Drop,
+    Sha256,
+    Hash256,                            // SHA-256 duplo
+    Equal,
+    EqualVerify,
+    Verify,                             // Falha se o topo for falso
+    CheckSig,                           // <assinatura> <chave> -> bool
+    CheckSigVerify,
+    CheckMultiSig,                      // <assinaturas...> m <chaves...> n -> bool
+    CheckMultiSigVerify,
+    CheckLockTimeVerify,                // O lock_time da transação precisa alcançar o valor no topo
+    CheckSequenceVerify,                // Só gasta após N blocos de confirmação do output gasto
+    If,                                 // Executa o bloco seguinte se o topo for verdadeiro
+    Else,
+    EndIf,
+}
+
+/// Sequência de instruções: trava um output (locking) ou destrava um input (unlocking)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Script(pub Vec<Op>);
+
+/// Termos de um HTLC (hash time-locked contract)
+///
+/// O destinatário gasta revelando o segredo cujo SHA-256 é `hash_lock`;
+/// depois da altura `timeout` quem financiou pode pegar o valor de volta
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Htlc {
+    pub payee: [u8; 32],                // Chave pública de quem recebe com o segredo
+    pub hash_lock: Hash,                // SHA-256 do segredo
+    pub refund: [u8; 32],               // Chave pública do reembolso
+    pub timeout: u64,                   // Reembolso vale em blocos acima desta altura
+}
+
+/// Limites de recursos do interpretador, parte do consenso
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptLimits {
+    pub max_script_size: usize,         // Bytes serializados de cada script
+    pub max_ops: usize,                 // Instruções que não são push (chaves do multisig contam)
+    pub max_stack_size: usize,          // Elementos na pilha
+    pub max_element_size: usize,        // Bytes de um elemento da pilha
+    pub max_multisig_keys: usize,
+}
+
+/// Onde o script está sendo avaliado: qual input gasta qual output
+pub struct ScriptContext<'a> {
+    pub tx: &'a Transaction,
+    pub input_index: usize,
+    pub spent: &'a UtxoEntry,
+    pub height: u64,                    // Altura do bloco em que a transação entra
+}
+
+impl Script {
+    pub fn new(ops: Vec<Op>) -> Self {
+        Script(ops)
+    }
+
+    /// Trava o output no hash da chave pública:
+    /// `DUP SHA256 <hash> EQUALVERIFY CHECKSIG`
+    ///
+    /// O desbloqueio é `<assinatura> <chave>`, então a chave só é revelada no gasto
+    pub fn pay_to_pubkey_hash(pubkey_hash: &Hash) -> Self {
+        Script(vec![
+            Op::Dup,
+            Op::Sha256,
+            Op::PushData(pubkey_hash.as_bytes().to_vec()),
+            Op::EqualVerify,
+            Op::CheckSig,
+        ])
+    }
+
+    /// Hash da chave pública se o script for do formato `pay_to_pubkey_hash`
+    pub fn pubkey_hash(&self) -> Option<Hash> {
+        let [Op::Dup, Op::Sha256, Op::PushData(hash), Op::EqualVerify, Op::CheckSig] = self.0.as_slice() else {
+            return None;
+        };
+        <[u8; 32]>::try_from(hash.as_slice()).ok().map(Hash::from_bytes)
+    }
+
+    /// Trava o output em M de N chaves públicas:
+    /// `M <chave 1> ... <chave N> N CHECKMULTISIG`
+    ///
+    /// As assinaturas do desbloqueio precisam vir na mesma ordem das chaves
+    pub fn multisig(threshold: usize, pubkeys: &[VerifyingKey]) -> Self {
+        let mut ops = vec![Op::PushInt(threshold as i64)];
+        ops.extend(pubkeys.iter().map(|key| Op::PushData(key.as_bytes().to_vec())));
+        ops.push(Op::PushInt(pubkeys.len() as i64));
+        ops.push(Op::CheckMultiSig);
+        Script(ops)
+    }
+
+    /// Limiar M e as chaves se o script for do formato `multisig`
+    pub fn as_multisig(&self) -> Option<(usize, Vec<Vec<u8>>)> {
+        let [Op::PushInt(threshold), middle @ .., Op::PushInt(count), Op::CheckMultiSig] = self.0.as_slice() else {
+            return None;
+        };
+        let pubkeys: Vec<Vec<u8>> = middle.iter()
+            .map(|op| match op { Op::PushData(key) => Some(key.clone()), _ => None })
+            .collect::<Option<_>>()?;
+        if *count != pubkeys.len() as i64 || *threshold < 0 {
+            return None;
+        }
+        Some((*threshold as usize, pubkeys))
+    }
+
+    /// Trava o output num HTLC:
+    /// `IF SHA256 <hash> EQUALVERIFY <destinatário> ELSE <prazo> CHECKLOCKTIMEVERIFY <reembolso> ENDIF CHECKSIG`
+    ///
+    /// O desbloqueio escolhe o caminho: `<assinatura> <segredo> 1` ou `<assinatura> 0`
+    pub fn htlc(htlc: &Htlc) -> Self {
+        Script(vec![
+            Op::If,
+            Op::Sha256,
+            Op::PushData(htlc.hash_lock.as_bytes().to_vec()),
+            Op::EqualVerify,
+            Op::PushData(htlc.payee.to_vec()),
+            Op::Else,
+            Op::PushInt(htlc.timeout as i64),
+            Op::CheckLockTimeVerify,
+            Op::PushData(htlc.refund.to_vec()),
+            Op::EndIf,
+            Op::CheckSig,
+        ])
+    }
+
+    /// Termos do contrato se o script for do formato `htlc`
+    pub fn as_htlc(&self) -> Option<Htlc> {
+        let [Op::If, Op::Sha256, Op::PushData(hash_lock), Op::EqualVerify, Op::PushData(payee),
+             Op::Else, Op::PushInt(timeout), Op::CheckLockTimeVerify, Op::PushData(refund),
+             Op::EndIf, Op::CheckSig] = self.0.as_slice() else {
+            return None;
+        };
+        let hash_lock = <[u8; 32]>::try_from(hash_lock.as_slice()).ok()?;
+        if *timeout < 0 {
+            return None;
+        }
+        Some(Htlc {
+            payee: payee.as_slice().try_into().ok()?,
+            hash_lock: Hash::from_bytes(hash_lock),
+            refund: refund.as_slice().try_into().ok()?,
+            timeout: *timeout as u64,
+        })
+    }
+
+    /// Só empilha dados (exigido dos scripts de desbloqueio)
+    pub fn is_push_only(&self) -> bool {
+        self.0.iter().all(|op| matches!(op, Op::PushData(_) | Op::PushInt(_)))
+    }
+
+    pub fn serialized_size(&self) -> usize {
+        bincode::serialized_size(self).expect("Falha ao serializar com bincode") as usize
+    }
+}
+
+/// Avalia o script de desbloqueio do input seguido do script do output gasto
+///
+/// O desbloqueio só pode empilhar dados; a pilha resultante é a entrada do
+/// script de bloqueio, que precisa terminar com um único valor verdadeiro.
+/// Sem essa regra, qualquer um poderia empilhar dados extras num input já
+/// assinado e mudar o txid (o `script_sig` entra no hash) sem invalidá-lo
+pub fn verify_script(ctx: &ScriptContext, limits: &ScriptLimits) -> Result<(), ScriptError> {
+    let input = ctx.tx.inputs.get(ctx.input_index).ok_or(ScriptError::MissingInput)?;
+    let unlocking = &input.script_sig;
+    let locking = &ctx.spent.output.script_pubkey;
+
+    for script in [unlocking, locking] {
+        let size = script.serialized_size();
+        if size > limits.max_script_size {
+            return Err(ScriptError::ScriptTooLarge { size, max: limits.max_script_size });
+        }
+    }
+    if !unlocking.is_push_only() {
+        return Err(ScriptError::NotPushOnly);
+    }
+
+    let mut machine = Machine { stack: Vec::new(), conditions: Vec::new(), ops: 0, ctx, limits };
+    machine.run(unlocking)?;
+    machine.run(locking)?;
+    match machine.stack.as_slice() {
+        [top] if is_true(top) => Ok(()),
+        [.., top] if is_true(top) => Err(ScriptError::CleanStack { size: machine.stack.len() }),
+        _ => Err(ScriptError::EvalFalse),
+    }
+}
+
+/// Estado do interpretador durante uma avaliação
+struct Machine<'a> {
+    stack: Vec<Vec<u8>>,
+    conditions: Vec<bool>,              // Um nível por IF aberto: se o ramo atual executa
+    ops: usize,
+    ctx: &'a ScriptContext<'a>,
+    limits: &'a ScriptLimits,
+}
+
+impl Machine<'_> {
+    fn run(&mut self, script: &Script) -> Result<(), ScriptError> {
+        for op in &script.0 {
+            if !matches!(op, Op::PushData(_) | Op::PushInt(_)) {
+                self.count_ops(1)?;
+            }
+            let executing = self.conditions.iter().all(|&branch| branch);
+            match op {
+                Op::If => {
+                    // Num ramo que não executa o IF não consome a pilha
+                    let condition = executing && is_true(&self.pop()?);
+                    self.conditions.push(condition);
+                }
+                Op::Else => {
+                    let branch = self.conditions.last_mut().ok_or(ScriptError::UnbalancedConditional)?;
+                    *branch = !*branch;
+                }
+                Op::EndIf => {
+                    self.conditions.pop().ok_or(ScriptError::UnbalancedConditional)?;
+                }
+                _ if executing => self.step(op)?,
+                _ => {}
+            }
+            if self.stack.len() > self.limits.max_stack_size {
+                return Err(ScriptError::StackOverflow { max: self.limits.max_stack_size });
+            }
+        }
+        // Um condicional não pode atravessar de um script para o outro
+        if !self.conditions.is_empty() {
+            return Err(ScriptError::UnbalancedConditional);
+        }
+        Ok(())
+    }
+
+    fn step(&mut self, op: &Op) -> Result<(), ScriptError> {
+        match op {
+            Op::PushData(data) => self.push(data.clone())?,
+            Op::PushInt(n) => self.push(encode_num(*n))?,
+            Op::Dup => {
+                let top = self.stack.last().ok_or(ScriptError::StackUnderflow)?.clone();
+                self.push(top)?;
+            }
+            Op::Drop => { self.pop()?; }
+            Op::Sha256 => {
+                let data = self.pop()?;
+                self.push(Sha256::digest(&data).to_vec())?;
+            }
+            Op::Hash256 => {
+                let data = self.pop()?;
+                self.push(Sha256::digest(Sha256::digest(&data)).to_vec())?;
+            }
+            Op::Equal | Op::EqualVerify => {
+                let a = self.pop()?;
+                let b = self.pop()?;
+                self.finish(op == &Op::EqualVerify, a == b, ScriptError::EqualVerifyFailed)?;
+            }
+            Op::Verify => {
+                if !is_true(&self.pop()?) {
+                    return Err(ScriptError::VerifyFailed);
+                }
+            }
+            Op::CheckSig | Op::CheckSigVerify => {
+                let pubkey = self.pop()?;
+                let signature = self.pop()?;
+                let valid = self.check_signature(&signature, &pubkey);
+                self.finish(op == &Op::CheckSigVerify, valid, ScriptError::CheckSigVerifyFailed)?;
+            }
+            Op::CheckMultiSig | Op::CheckMultiSigVerify => {
+                let valid = self.check_multisig()?;
+                self.finish(op == &Op::CheckMultiSigVerify, valid, ScriptError::CheckSigVerifyFailed)?;
+            }
+            Op::CheckLockTimeVerify => {
+                let required = self.pop_num()?;
+                if required < 0 {
+                    return Err(ScriptError::NegativeLockTime);
+                }
+                self.check_lock_time(required as u64)?;
+            }
+            Op::If | Op::Else | Op::EndIf => unreachable!("condicionais são tratados em run"),
+            Op::CheckSequenceVerify => {
+                let required = self.pop_num()?;
+                if required < 0 {
+                    return Err(ScriptError::NegativeLockTime);
+                }
+                let confirmations = self.ctx.height.saturating_sub(self.ctx.spent.height);
+                if confirmations < required as u64 {
+                    return Err(ScriptError::SequenceNotReached { required: required as u64, confirmations });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Empilha o resultado, ou falha na hora nas variantes *Verify
+    fn finish(&mut self, verify: bool, result: bool, error: ScriptError) -> Result<(), ScriptError> {
+        if verify {
+            return if result { Ok(()) } else { Err(error) };
+        }
+        self.push(if result { vec![1] } else { Vec::new() })
+    }
+
+    /// O script só compara com o `lock_time` da transação; quem garante que
+    /// ele já passou é `Transaction::is_final`, na validação do bloco
+    fn check_lock_time(&self, required: u64) -> Result<(), ScriptError> {
+        let lock_time = self.ctx.tx.lock_time;
+        if (required < LOCKTIME_THRESHOLD) != (lock_time < LOCKTIME_THRESHOLD) {
+            return Err(ScriptError::LockTimeTypeMismatch { required, lock_time });
+        }
+        if lock_time < required {
+            return Err(ScriptError::LockTimeNotReached { required, lock_time });
+        }
+        // Se todos os inputs tiverem sequence final, `is_final` ignora o lock_time;
+        // exigir uma não final aqui garante que a trava vale para a transação
+        if self.ctx.tx.inputs[self.ctx.input_index].sequence == SEQUENCE_FINAL {
+            return Err(ScriptError::FinalSequence);
+        }
+        Ok(())
+    }
+
+    /// `m` assinaturas precisam bater, na ordem, com `m` das `n` chaves
+    fn check_multisig(&mut self) -> Result<bool, ScriptError> {
+        let key_count = self.pop_num()?;
+        if key_count < 0 || key_count as usize > self.limits.max_multisig_keys {
+            return Err(ScriptError::InvalidKeyCount { count: key_count, max: self.limits.max_multisig_keys });
+        }
+        let key_count = key_count as usize;
+        self.count_ops(key_count)?;
+        let mut pubkeys = (0..key_count).map(|_| self.pop()).collect::<Result<Vec<_>, _>>()?;
+        pubkeys.reverse();
+
+        let sig_count = self.pop_num()?;
+        if sig_count < 0 || sig_count as usize > key_count {
+            return Err(ScriptError::InvalidSignatureCount { count: sig_count, keys: key_count });
+        }
+        let mut signatures = (0..sig_count).map(|_| self.pop()).collect::<Result<Vec<_>, _>>()?;
+        signatures.reverse();
+
+        // Cada chave é usada no máximo uma vez, sempre avançando
+        let mut keys = pubkeys.iter();
+        Ok(signatures.iter().all(|signature| keys.any(|pubkey| self.check_signature(signature, pubkey))))
+    }
+
+    fn check_signature(&self, signature: &[u8], pubkey: &[u8]) -> bool {
+        let Ok(key_bytes) = <[u8; 32]>::try_from(pubkey) else { return false; };
+        let Ok(public_key) = VerifyingKey::from_bytes(&key_bytes) else { return false; };
+        let Ok(signature) = Signature::from_slice(signature) else { return false; };
+        let Some(data) = self.ctx.tx.get_data_to_sign(self.ctx.input_index, &self.ctx.spent.output) else { return false; };
+        public_key.verify(&data, &signature).is_ok()
+    }
+
+    fn count_ops(&mut self, count: usize) -> Result<(), ScriptError> {
+        self.ops += count;
+        if self.ops > self.limits.max_ops {
+            return Err(ScriptError::TooManyOps { max: self.limits.max_ops });
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, data: Vec<u8>) -> Result<(), ScriptError> {
+        if data.len() > self.limits.max_element_size {
+            return Err(ScriptError::ElementTooLarge { size: data.len(), max: self.limits.max_element_size });
+        }
+        self.stack.push(data);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<Vec<u8>, ScriptError> {
+        self.stack.pop().ok_or(ScriptError::StackUnderflow)
+    }
+
+    fn pop_num(&mut self) -> Result<i64, ScriptError> {
+        let data = self.pop()?;
+        decode_num(&data).ok_or(ScriptError::InvalidNumber)
+    }
+}
+
+fn is_true(data: &[u8]) -> bool {
+    data.iter().any(|&byte| byte != 0)
+}
+
+fn encode_num(n: i64) -> Vec<u8> {
+    n.to_le_bytes().to_vec()
+}
+
+/// Aceita de 0 a 8 bytes little-endian, estendendo o sinal
+fn decode_num(data: &[u8]) -> Option<i64> {
+    if data.len() > 8 {
+        return None;
+    }
+    let negative = data.last().is_some_and(|&byte| byte & 0x80 != 0);
+    let mut bytes = if negative { [0xff; 8] } else { [0; 8] };
+    bytes[..data.len()].copy_from_slice(data);
+    Some(i64::from_le_bytes(bytes))
+}
@@ -0,0 +1,31 @@
+use serde::{Serialize, Deserialize};
+
+/// Quais outputs uma assinatura compromete
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SigHashBase {
+    #[default]
+    All,                                // Todos os outputs
+    None,                               // Nenhum output (qualquer um pode mudar o destino)
+    Single,                             // Só o output com o mesmo índice do input
+}
+
+/// Modo de assinatura de um input, no estilo dos sighash flags do Bitcoin
+///
+/// Com `anyone_can_pay` a assinatura compromete apenas o próprio input,
+/// permitindo que outras partes adicionem inputs depois
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SigHashType {
+    pub base: SigHashBase,
+    pub anyone_can_pay: bool,
+}
+
+impl SigHashType {
+    pub const ALL: SigHashType = SigHashType { base: SigHashBase::All, anyone_can_pay: false };
+    pub const NONE: SigHashType = SigHashType { base: SigHashBase::None, anyone_can_pay: false };
+    pub const SINGLE: SigHashType = SigHashType { base: SigHashBase::Single, anyone_can_pay: false };
+
+    /// Aplica o modificador ANYONECANPAY
+    pub fn anyone_can_pay(self) -> Self {
+        SigHashType { anyone_can_pay: true, ..self }
+    }
+}
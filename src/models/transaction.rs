@@ -1,98 +1,273 @@
-use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
 use serde::{Serialize, Deserialize};
+use crate::crypto::address::Address;
 use crate::crypto::hashing::Hash;
+use crate::models::blockchain::UtxoEntry;
+use crate::models::errors::ScriptError;
+use crate::models::script::{self, Htlc, Op, Script, ScriptContext, ScriptLimits};
+use crate::models::sighash::{SigHashBase, SigHashType};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
     pub inputs: Vec<Input>,
     pub outputs: Vec<Output>,  
+    #[serde(default)]
+    pub lock_time: u64,                         // 0, altura ou timestamp (ver LOCKTIME_THRESHOLD)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Input {
     pub prev_tx_hash: Hash,           // Hash da transação anterior
     pub output_index: usize,
-    pub signature: Option<Vec<u8>>,            // Output daquela transação
+    #[serde(default)]
+    pub script_sig: Script,                     // Desbloqueia o script do output gasto
+    #[serde(default = "default_sequence")]
+    pub sequence: u32,                          // Trava relativa e sinal de replace-by-fee
+    #[serde(default)]
+    pub sighash: SigHashType,                   // O que a assinatura deste input compromete
+    #[serde(default)]
+    pub coinbase: Option<CoinbaseData>,         // Só no input da coinbase
+}
+
+/// Dados que o input da coinbase carrega no lugar de uma assinatura
+///
+/// A altura torna cada coinbase única, mesmo pagando o mesmo valor ao mesmo minerador
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoinbaseData {
+    pub height: u64,
+    pub extra: Vec<u8>,                         // Livre para o minerador (até MAX_COINBASE_EXTRA bytes)
+}
+
+pub const MAX_COINBASE_EXTRA: usize = 100;
+
+/// Sequence padrão: o input não aceita ser substituído, e se todos os inputs
+/// a usarem o `lock_time` da transação é ignorado
+pub const SEQUENCE_FINAL: u32 = u32::MAX;
+
+/// Sequence que sinaliza opt-in para replace-by-fee
+pub const SEQUENCE_REPLACEABLE: u32 = SEQUENCE_FINAL - 2;
+
+/// Com este bit ligado a sequence não impõe trava relativa
+pub const SEQUENCE_LOCKTIME_DISABLE_FLAG: u32 = 1 << 31;
+
+/// Bits da sequence com a idade mínima (em blocos) do output gasto
+pub const SEQUENCE_LOCKTIME_MASK: u32 = 0xffff;
+
+/// `lock_time` abaixo disso é altura de bloco; a partir disso, timestamp Unix
+pub const LOCKTIME_THRESHOLD: u64 = 500_000_000;
+
+fn default_sequence() -> u32 {
+    SEQUENCE_FINAL
+}
+
+impl Input {
+    /// Input ainda não assinado que gasta `output_index` de `prev_tx_hash`
+    pub fn new(prev_tx_hash: Hash, output_index: usize) -> Self {
+        Input {
+            prev_tx_hash,
+            output_index,
+            script_sig: Script::default(),
+            sequence: SEQUENCE_FINAL,
+            sighash: SigHashType::ALL,
+            coinbase: None,
+        }
+    }
+
+    /// Input que só pode ser minerado `blocks` blocos depois do output que ele gasta
+    pub fn with_relative_lock(prev_tx_hash: Hash, output_index: usize, blocks: u16) -> Self {
+        Input { sequence: blocks as u32, ..Input::new(prev_tx_hash, output_index) }
+    }
+
+    /// Idade mínima (em blocos) exigida do output gasto, se houver
+    pub fn relative_lock(&self) -> Option<u64> {
+        if self.sequence & SEQUENCE_LOCKTIME_DISABLE_FLAG != 0 {
+            return None;
+        }
+        Some((self.sequence & SEQUENCE_LOCKTIME_MASK) as u64)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Output {
     pub value: u64,
-    pub pubkey: String,                 // Endereço do destinatário
+    pub script_pubkey: Script,          // Condição para gastar este output
+}
+
+impl Output {
+    /// Output que só o dono da chave por trás de `address` pode gastar
+    pub fn to_address(value: u64, address: &Address) -> Self {
+        Output { value, script_pubkey: Script::pay_to_pubkey_hash(&address.pubkey_hash) }
+    }
+
+    /// Output que exige `threshold` assinaturas entre as chaves públicas `pubkeys`
+    pub fn multisig(value: u64, threshold: usize, pubkeys: &[VerifyingKey]) -> Self {
+        Output { value, script_pubkey: Script::multisig(threshold, pubkeys) }
+    }
+
+    /// Output travado num HTLC: segredo + chave do destinatário, ou chave de reembolso após o prazo
+    pub fn htlc(value: u64, htlc: &Htlc) -> Self {
+        Output { value, script_pubkey: Script::htlc(htlc) }
+    }
+
+    /// Hash da chave dona do output, se ele for pago a um endereço
+    pub fn pubkey_hash(&self) -> Option<Hash> {
+        self.script_pubkey.pubkey_hash()
+    }
 }
 
 
 
 impl Transaction {
+    /// Transação sem trava de tempo
+    pub fn new(inputs: Vec<Input>, outputs: Vec<Output>) -> Self {
+        Transaction { inputs, outputs, lock_time: 0 }
+    }
+
     pub fn calculate_hash(&self) -> Hash {
         let bytes = bincode::serialize(self).expect("falha no servidor");
         Hash::hash_data(&bytes)
     }
 
-    pub fn coinbase(to: &str, amount: u64) -> Self {
-        Transaction { 
-            inputs: vec![Input {
-                prev_tx_hash: Hash::new_empty(),
-                output_index: 0,
-                signature: None,        // Inicialmente sem assinatura
-            }], 
-            outputs: vec![Output {
-                value: amount,
-                pubkey: to.to_string(),
-            }], 
-        }
+    /// Tamanho em bytes da transação serializada (o que trafega e é guardado)
+    pub fn serialized_size(&self) -> usize {
+        bincode::serialized_size(self).expect("Falha ao serializar com bincode") as usize
+    }
+
+    pub fn coinbase(to: &Address, amount: u64, height: u64) -> Self {
+        Self::coinbase_with_extra(to, amount, height, Vec::new())
+    }
+
+    /// Coinbase com dados extras livres do minerador (ex.: identificação do pool)
+    pub fn coinbase_with_extra(to: &Address, amount: u64, height: u64, extra: Vec<u8>) -> Self {
+        let outputs = vec![Output::to_address(amount, to)];
+        Self::coinbase_split(outputs, height, extra)
+    }
+
+    /// Coinbase que paga vários outputs (ex.: membros de um pool)
+    pub fn coinbase_split(outputs: Vec<Output>, height: u64, extra: Vec<u8>) -> Self {
+        Transaction::new(
+            vec![Input {
+                coinbase: Some(CoinbaseData { height, extra }),
+                ..Input::new(Hash::new_empty(), 0)
+            }],
+            outputs,
+        )
     }
 
 
     /// Previne Replay Attacks garantindo que o conjunto de dados serializado
-    /// Gera a mensagem que será assinada
-    pub fn get_data_to_sign(&self) -> Vec<u8> {
-        // Cria uma cópia da transação sem a assinatura
+    /// Gera a mensagem que será assinada pelo input `input_index`
+    ///
+    /// Além da transação sem assinaturas, compromete o output gasto (valor e
+    /// dono), então uma assinatura não pode ser reaproveitada sobre outro output.
+    /// O sighash do input decide quais inputs e outputs entram na mensagem.
+    /// Retorna `None` se o input não existe ou se é SINGLE sem output par
+    pub fn get_data_to_sign(&self, input_index: usize, spent: &Output) -> Option<Vec<u8>> {
+        let sighash = self.inputs.get(input_index)?.sighash;
+
+        // Cria uma cópia da transação sem os scripts de desbloqueio (onde ficam as assinaturas)
         let mut temp_tx = self.clone();
         for input in temp_tx.inputs.iter_mut() {
-            input.signature = None;
+            input.script_sig = Script::default();
+        }
+
+        // ANYONECANPAY: só o próprio input, os demais podem ser adicionados depois
+        let committed_index = if sighash.anyone_can_pay {
+            temp_tx.inputs = vec![temp_tx.inputs.swap_remove(input_index)];
+            None
+        } else {
+            Some(input_index)
+        };
+
+        match sighash.base {
+            SigHashBase::All => {}
+            SigHashBase::None => temp_tx.outputs.clear(),
+            SigHashBase::Single => {
+                let paired = temp_tx.outputs.get(input_index)?.clone();
+                temp_tx.outputs = vec![paired];
+            }
         }
-        bincode::serialize(&temp_tx).expect("Falha ao serializar com bincode")
+
+        Some(bincode::serialize(&(&temp_tx, committed_index, spent, sighash))
+            .expect("Falha ao serializar com bincode"))
     }
 
+    /// Assinatura do input `input_index` para montar scripts de desbloqueio
+    ///
+    /// Usa o `sighash` já definido no input; `spent` é o output que ele consome
+    pub fn signature_for(&self, input_index: usize, spent: &Output, secret_key: &SigningKey) -> Option<Vec<u8>> {
+        let data = self.get_data_to_sign(input_index, spent)?;
+        Some(secret_key.sign(&data).to_vec())
+    }
 
-    pub fn sign(&mut self, secret_key: &SigningKey) {
-        let data = self.get_data_to_sign();
-        let signature = secret_key.sign(&data);
+    /// Assina um input que gasta um output pago a um endereço (`pay_to_pubkey_hash`)
+    ///
+    /// O script de desbloqueio vira a assinatura seguida da chave pública,
+    /// revelada só agora. Retorna `false` se não foi possível assinar
+    pub fn sign_input(&mut self, input_index: usize, spent: &Output, secret_key: &SigningKey) -> bool {
+        let Some(signature) = self.signature_for(input_index, spent, secret_key) else { return false; };
+        let pubkey = secret_key.verifying_key().to_bytes().to_vec();
+        self.inputs[input_index].script_sig = Script::new(vec![Op::PushData(signature), Op::PushData(pubkey)]);
+        true
+    }
 
-        // Coloca a mesma assinatura em todos os inputs
-        for input in self.inputs.iter_mut() {
-            input.signature = Some(signature.to_vec());
-        }
+    /// Define o modo de assinatura de um input e o assina
+    pub fn sign_input_with(&mut self, input_index: usize, spent: &Output, secret_key: &SigningKey, sighash: SigHashType) -> bool {
+        let Some(input) = self.inputs.get_mut(input_index) else { return false; };
+        input.sighash = sighash;
+        self.sign_input(input_index, spent, secret_key)
     }
 
-    pub fn is_coinbase(&self) -> bool {
-        self.inputs.len() == 1 && self.inputs[0].prev_tx_hash.is_empty()
+    /// Atalho para quando todos os inputs pertencem à mesma chave
+    ///
+    /// `spent_outputs[i]` é o output gasto pelo input `i`
+    pub fn sign(&mut self, secret_key: &SigningKey, spent_outputs: &[Output]) {
+        for (input_index, spent) in spent_outputs.iter().enumerate().take(self.inputs.len()) {
+            self.sign_input(input_index, spent, secret_key);
+        }
     }
 
-    pub fn verify(&self, public_key: &VerifyingKey) -> bool {
-        if self.inputs.is_empty() { return false; }
+    /// Opt-in RBF: algum input pede para a transação poder ser substituída
+    /// por outra que pague mais enquanto estiver na mempool
+    pub fn is_replaceable(&self) -> bool {
+        self.inputs.iter().any(|input| input.sequence < SEQUENCE_FINAL - 1)
+    }
 
-        if self.is_coinbase(){
+    /// A trava absoluta já passou para um bloco na altura `height`?
+    ///
+    /// `median_time_past` é o do branch antes desse bloco, então o minerador
+    /// não consegue adiantar a liberação mexendo no próprio timestamp.
+    /// Como no Bitcoin, inputs todos com `SEQUENCE_FINAL` desligam a trava
+    pub fn is_final(&self, height: u64, median_time_past: i64) -> bool {
+        if self.lock_time == 0 {
             return true;
         }
-        let data = self.get_data_to_sign();
-
-        for input in &self.inputs {
-            if let Some(sig_bytes) = &input.signature {
-                // Tenta reconstruir a assinatura a partir dos bytes
-                let signature = match Signature::from_slice(sig_bytes) {
-                    Ok(s) => s,
-                    Err(_) => return false,    
-                };
-                // Verifica se a assinatura bate com a chave pública e os dados
-                if public_key.verify(&data, &signature).is_err() {
-                    return false;
-                }
-            } else {
-                return false;
-            }
+        if self.inputs.iter().all(|input| input.sequence == SEQUENCE_FINAL) {
+            return true;
         }
-        true
+        if self.lock_time < LOCKTIME_THRESHOLD {
+            self.lock_time < height
+        } else {
+            // Comparar em u64: lock_time acima de i64::MAX nunca vira negativo
+            self.lock_time < median_time_past.max(0) as u64
+        }
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        self.inputs.len() == 1 && self.inputs[0].prev_tx_hash.is_empty()
+    }
+
+    /// Altura comprometida pela coinbase, se houver
+    pub fn coinbase_height(&self) -> Option<u64> {
+        if !self.is_coinbase() { return None; }
+        self.inputs[0].coinbase.as_ref().map(|data| data.height)
+    }
+
+    /// Roda o script de desbloqueio do input contra o do output que ele gasta
+    ///
+    /// `height` é a altura do bloco em que a transação entra (usada pelos timelocks)
+    pub fn verify_input(&self, input_index: usize, spent: &UtxoEntry, height: u64, limits: &ScriptLimits) -> Result<(), ScriptError> {
+        let ctx = ScriptContext { tx: self, input_index, spent, height };
+        script::verify_script(&ctx, limits)
     }
-}
\ No newline at end of file
+}
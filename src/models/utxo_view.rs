@@ -0,0 +1,55 @@
+use std::collections::{HashMap, HashSet};
+use crate::models::blockchain::{UTXOKey, UtxoEntry};
+use crate::models::transaction::Transaction;
+
+/// Visão temporária do UTXO set
+///
+/// Aplica transações por cima do UTXO set confirmado sem alterá-lo,
+/// para validar um bloco (ou a mempool) transação por transação
+pub struct UtxoView<'a> {
+    base: &'a HashMap<UTXOKey, UtxoEntry>,
+    added: HashMap<UTXOKey, UtxoEntry>,     // Outputs criados dentro da visão
+    spent: HashSet<UTXOKey>,                // Outputs gastos dentro da visão
+    pub height: u64,                        // Altura do bloco em que as transações entrariam
+}
+
+impl<'a> UtxoView<'a> {
+    pub fn new(base: &'a HashMap<UTXOKey, UtxoEntry>, height: u64) -> Self {
+        UtxoView { base, added: HashMap::new(), spent: HashSet::new(), height }
+    }
+
+    pub fn get(&self, key: &UTXOKey) -> Option<&UtxoEntry> {
+        if self.spent.contains(key) {
+            return None;
+        }
+        self.added.get(key).or_else(|| self.base.get(key))
+    }
+
+    /// Gasta os inputs e cria os outputs da transação dentro da visão
+    pub fn apply(&mut self, tx: &Transaction) {
+        if !tx.is_coinbase() {
+            for input in &tx.inputs {
+                let key = UTXOKey { tx_hash: input.prev_tx_hash, output_index: input.output_index };
+                if self.added.remove(&key).is_none() {
+                    self.spent.insert(key);
+                }
+            }
+        }
+
+        self.add_outputs(tx);
+    }
+
+    /// Só cria os outputs da transação, sem gastar os inputs
+    ///
+    /// Usado para enxergar outputs da mempool sem esconder os que ela gasta,
+    /// já que conflitos com a mempool são decididos pela política de RBF
+    pub fn add_outputs(&mut self, tx: &Transaction) {
+        let tx_hash = tx.calculate_hash();
+        for (output_index, output) in tx.outputs.iter().enumerate() {
+            let key = UTXOKey { tx_hash, output_index };
+            self.spent.remove(&key);
+            let entry = UtxoEntry { output: output.clone(), height: self.height, is_coinbase: tx.is_coinbase() };
+            self.added.insert(key, entry);
+        }
+    }
+}
@@ -1,16 +1,125 @@
+use std::fmt;
+use std::io;
 use serde::{Serialize, Deserialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use crate::crypto::hashing::Hash;
 use crate::models::block::Block;
+use crate::models::blockchain::Blockchain;
+use crate::models::chain_params::ChainParams;
 use crate::models::transaction::Transaction;
 
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Message {
     DiscoverNodes,                  // Quem está online
-    Version(usize),                 // Eu tenho x blocos. E voce ?
+    Version(VersionInfo),           // Eu tenho x blocos. E voce ?
     Subscribe,                      // Me avise de novas transações
     NewTransaction(Transaction),    // Acabei de receber essa transação
     NewBlock(Block),                // Achei um novo bloco
     RequestChain,                   // me mande um blockchain completo
     FullChain(Vec<Block>),          //Aqui esta a minha chain
-} 
+}
 
+/// Maior mensagem aceita de um peer, para um prefixo forjado não alocar gigabytes
+pub const MAX_MESSAGE_SIZE: usize = 32 * 1024 * 1024;
+
+/// Apresentação enviada ao conectar com um peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub magic: [u8; 4],
+    pub genesis_hash: Hash,
+    pub height: usize,
+}
+
+/// Motivos para recusar um peer durante o handshake
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandshakeError {
+    WrongNetwork { expected: [u8; 4], found: [u8; 4] },
+    GenesisMismatch { expected: Hash, found: Hash },
+    UnexpectedMessage,                                          // Primeira mensagem não foi `Version`
+    Disconnected,                                               // Conexão caiu ou mandou lixo antes do `Version`
+}
+
+impl VersionInfo {
+    pub fn from_chain(blockchain: &Blockchain) -> Self {
+        VersionInfo {
+            magic: blockchain.params.magic,
+            genesis_hash: blockchain.params.genesis_hash(),
+            height: blockchain.chain.len(),
+        }
+    }
+
+    /// Só faz sentido sincronizar com peers da mesma rede e do mesmo gênesis
+    pub fn check(&self, params: &ChainParams) -> Result<(), HandshakeError> {
+        if self.magic != params.magic {
+            return Err(HandshakeError::WrongNetwork { expected: params.magic, found: self.magic });
+        }
+        let expected = params.genesis_hash();
+        if self.genesis_hash != expected {
+            return Err(HandshakeError::GenesisMismatch { expected, found: self.genesis_hash });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for HandshakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandshakeError::WrongNetwork { expected, found } =>
+                write!(f, "Peer de outra rede: magic {} em vez de {}", hex::encode(found), hex::encode(expected)),
+            HandshakeError::GenesisMismatch { expected, found } =>
+                write!(f, "Peer com gênesis {} diferente do nosso {}", found, expected),
+            HandshakeError::UnexpectedMessage =>
+                write!(f, "Peer não se apresentou com Version"),
+            HandshakeError::Disconnected =>
+                write!(f, "Peer desconectou durante o handshake"),
+        }
+    }
+}
+
+impl std::error::Error for HandshakeError {}
+
+/// Envia uma mensagem: tamanho (u32 big-endian) seguido do bincode
+pub async fn write_message<W: AsyncWrite + Unpin>(stream: &mut W, message: &Message) -> io::Result<()> {
+    let bytes = bincode::serialize(message).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if bytes.len() > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Mensagem grande demais"));
+    }
+    stream.write_all(&(bytes.len() as u32).to_be_bytes()).await?;
+    stream.write_all(&bytes).await?;
+    stream.flush().await
+}
+
+/// Lê uma mensagem no formato de `write_message`
+pub async fn read_message<R: AsyncRead + Unpin>(stream: &mut R) -> io::Result<Message> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len).await?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Mensagem grande demais"));
+    }
+    let mut bytes = vec![0u8; len];
+    stream.read_exact(&mut bytes).await?;
+    bincode::deserialize(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Troca de `Version` ao abrir uma conexão, dos dois lados
+///
+/// Manda a nossa apresentação, lê a do peer e confere rede e gênesis.
+/// Com erro a conexão deve ser descartada antes de qualquer outra mensagem
+pub async fn handshake<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    ours: &VersionInfo,
+    params: &ChainParams,
+) -> Result<VersionInfo, HandshakeError> {
+    write_message(stream, &Message::Version(ours.clone())).await
+        .map_err(|_| HandshakeError::Disconnected)?;
+    match read_message(stream).await {
+        Ok(Message::Version(theirs)) => {
+            theirs.check(params)?;
+            Ok(theirs)
+        }
+        Ok(_) => Err(HandshakeError::UnexpectedMessage),
+        Err(_) => Err(HandshakeError::Disconnected),
+    }
+}
@@ -1,29 +1,52 @@
-use tokio::net::{TcpListener, TcpStream};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use crate::network::messages::Message;
+use tokio::net::TcpListener;
+use crate::models::blockchain::Blockchain;
+use crate::models::chain_params::ChainParams;
+use crate::network::messages::{handshake, read_message, VersionInfo};
 
 pub struct Node {
     pub address: String,
     pub known_peers: Vec<String>,
+    pub params: ChainParams,
+    pub version: VersionInfo,           // O que apresentamos a cada peer
 }
 
 impl Node {
-    pub async fn start_server(&self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn new(address: String, blockchain: &Blockchain) -> Self {
+        Node {
+            address,
+            known_peers: Vec::new(),
+            params: blockchain.params.clone(),
+            version: VersionInfo::from_chain(blockchain),
+        }
+    }
+
+    pub async fn start_server(&self) -> std::io::Result<()> {
         let listener = TcpListener::bind(&self.address).await?;
         println!("Node listening in: {}", self.address);
+        self.serve(listener).await
+    }
 
+    /// Aceita conexões num listener já aberto
+    ///
+    /// Todo peer passa pelo handshake antes de qualquer outra mensagem;
+    /// quem é de outra rede ou de outro gênesis é desconectado na hora
+    pub async fn serve(&self, listener: TcpListener) -> std::io::Result<()> {
         loop {
-            let (mut socket, addr) = listener.accept().await?;
+            let (mut socket, peer_addr) = listener.accept().await?;
+            let version = self.version.clone();
+            let params = self.params.clone();
             tokio::spawn(async move {
-                let mut buffer = [0; 4096];
-                match socket.read(&mut buffer).await {
-                    Ok(n) if n > 0 => {
-                        let msg_str = String::from_utf8_lossy(&buffer[..n]);
-                        println!(" Mensagem recebida: {}", msg_str);
+                match handshake(&mut socket, &version, &params).await {
+                    Ok(theirs) => println!(" Peer {} conectado com {} blocos", peer_addr, theirs.height),
+                    Err(e) => {
+                        println!(" Peer {} recusado: {}", peer_addr, e);
+                        return;
                     }
-                    _ => {}
+                }
+                while let Ok(message) = read_message(&mut socket).await {
+                    println!(" Mensagem de {}: {:?}", peer_addr, message);
                 }
             });
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,90 @@
+use utxo_blockchain::crypto::address::Address;
+use utxo_blockchain::crypto::hashing::Hash;
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::chain_params::Network;
+use utxo_blockchain::models::errors::AddressError;
+
+const NETWORKS: [Network; 3] = [Network::Mainnet, Network::Testnet, Network::Regtest];
+
+fn alice(network: Network) -> Address {
+    Wallet::from_seed("alice").address(network)
+}
+
+/// Troca o caractere em `position` por outro do alfabeto Base58
+fn with_typo(text: &str, position: usize) -> String {
+    let mut chars: Vec<char> = text.chars().collect();
+    chars[position] = if chars[position] == 'z' { 'y' } else { 'z' };
+    chars.into_iter().collect()
+}
+
+#[test]
+fn round_trips_on_each_network() {
+    for network in NETWORKS {
+        let address = alice(network);
+        assert_eq!(address.version, network.address_version());
+        assert_eq!(Address::parse(&address.to_string(), network), Ok(address));
+    }
+    // O mesmo dono tem um texto diferente em cada rede
+    assert_ne!(alice(Network::Mainnet).to_string(), alice(Network::Testnet).to_string());
+}
+
+#[test]
+fn typo_fails_the_checksum() {
+    let text = alice(Network::Mainnet).to_string();
+    for position in [1, text.len() / 2, text.len() - 1] {
+        assert_eq!(Address::parse(&with_typo(&text, position), Network::Mainnet), Err(AddressError::InvalidChecksum));
+    }
+}
+
+#[test]
+fn character_outside_base58_is_rejected() {
+    let text = alice(Network::Mainnet).to_string();
+    // 0, O, I e l ficam de fora por serem confundíveis
+    for character in ['0', 'O', 'I', 'l', '-'] {
+        let mut typo: Vec<char> = text.chars().collect();
+        typo[5] = character;
+        let typo: String = typo.into_iter().collect();
+        assert_eq!(
+            Address::parse(&typo, Network::Mainnet),
+            Err(AddressError::InvalidCharacter { character, position: 5 }),
+        );
+    }
+}
+
+#[test]
+fn wrong_length_is_rejected() {
+    let text = alice(Network::Mainnet).to_string();
+    assert!(matches!(Address::parse(&text[..text.len() - 3], Network::Mainnet), Err(AddressError::InvalidLength { .. })));
+    assert!(matches!(Address::parse(&format!("{}zz", text), Network::Mainnet), Err(AddressError::InvalidLength { .. })));
+    assert_eq!(Address::parse("", Network::Mainnet), Err(AddressError::InvalidLength { length: 0 }));
+}
+
+#[test]
+fn address_of_another_network_is_rejected() {
+    let text = alice(Network::Testnet).to_string();
+    assert_eq!(
+        Address::parse(&text, Network::Mainnet),
+        Err(AddressError::WrongNetwork {
+            expected: Network::Mainnet.address_version(),
+            found: Network::Testnet.address_version(),
+        }),
+    );
+    // Sem exigir rede, decodifica normalmente
+    assert_eq!(text.parse(), Ok(alice(Network::Testnet)));
+}
+
+#[test]
+fn leading_zero_bytes_survive_the_round_trip() {
+    // Versão 0x00 com hash começando em zeros: cada byte zero vira um '1'
+    let mut bytes = [0xabu8; 32];
+    bytes[..3].copy_from_slice(&[0, 0, 0]);
+    let address = Address::new(0x00, Hash::from_bytes(bytes));
+    let text = address.to_string();
+    assert!(text.starts_with("1111"));
+    assert!(!text.starts_with("11111"));
+    assert_eq!(text.parse(), Ok(address));
+
+    let zero = Address::new(0x00, Hash::from_bytes([0; 32]));
+    assert!(zero.to_string().starts_with(&"1".repeat(33)));
+    assert_eq!(zero.to_string().parse(), Ok(zero));
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::{address, mine, pay, regtest_chain, spend};
+use utxo_blockchain::crypto::hashing::Hash;
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::block::Block;
+use utxo_blockchain::models::block_template::BlockTemplate;
+use utxo_blockchain::models::blockchain::Blockchain;
+use utxo_blockchain::models::transaction::{Output, Transaction};
+
+fn values(block: &Block) -> Vec<u64> {
+    block.transactions[0].outputs.iter().map(|o| o.value).collect()
+}
+
+#[test]
+fn zero_total_weight_builds_nothing() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    let coinbase = mine(&mut bc, &alice);
+    bc.submit_transaction(pay(&alice, &coinbase, 0, &alice, 49)).unwrap();
+
+    let (a, b) = (address(&alice), address(&Wallet::from_seed("bob")));
+    assert!(bc.build_block_template(&[]).is_none());
+    assert!(bc.build_block_template(&[(&a, 0), (&b, 0)]).is_none());
+    assert!(bc.create_next_block_with_payouts(&[(&a, 0)]).is_none());
+    assert_eq!(bc.mempool.len(), 1);
+}
+
+#[test]
+fn reward_is_split_by_weight() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    let coinbase = mine(&mut bc, &alice);
+    bc.submit_transaction(pay(&alice, &coinbase, 0, &alice, 49)).unwrap();
+
+    // Subsídio 50 + taxa 1
+    let (a, b) = (address(&Wallet::from_seed("a")), address(&Wallet::from_seed("b")));
+    let mut block = bc.create_next_block_with_payouts(&[(&a, 1), (&b, 2)]).unwrap();
+    assert_eq!(values(&block), vec![17, 34]);
+    block.mine();
+    bc.add_block(block).unwrap();
+    assert!(bc.mempool.is_empty());
+}
+
+#[test]
+fn remainder_goes_to_first_and_zero_weights_are_skipped() {
+    let mut bc = regtest_chain();
+    let [a, b, c] = ["a", "b", "c"].map(|seed| address(&Wallet::from_seed(seed)));
+
+    let block = bc.create_next_block_with_payouts(&[(&a, 1), (&b, 1), (&c, 1)]).unwrap();
+    assert_eq!(values(&block), vec![18, 16, 16]);
+
+    let mut block = bc.create_next_block_with_payouts(&[(&a, 0), (&b, 5)]).unwrap();
+    assert_eq!(values(&block), vec![50]);
+    block.mine();
+    bc.add_block(block).unwrap();
+    assert_eq!(bc.get_balance(&b), 50);
+}
+
+/// Corrente com `count` coinbases maduras de alice
+fn funded_chain(count: usize) -> (Blockchain, Vec<Transaction>) {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    let coinbases = (0..count).map(|_| mine(&mut bc, &alice)).collect();
+    (bc, coinbases)
+}
+
+fn submit(bc: &mut Blockchain, tx: &Transaction) -> Hash {
+    bc.submit_transaction(tx.clone()).unwrap();
+    tx.calculate_hash()
+}
+
+fn template(bc: &Blockchain) -> BlockTemplate {
+    bc.build_block_template(&[(&address(&Wallet::from_seed("miner")), 1)]).unwrap()
+}
+
+#[test]
+fn higher_fee_rate_goes_first() {
+    let alice = Wallet::from_seed("alice");
+    let bob = Wallet::from_seed("bob");
+    let (mut bc, coinbases) = funded_chain(3);
+
+    let low = submit(&mut bc, &pay(&alice, &coinbases[0], 0, &bob, 49));
+    let high = submit(&mut bc, &pay(&alice, &coinbases[1], 0, &bob, 45));
+    let mid = submit(&mut bc, &pay(&alice, &coinbases[2], 0, &bob, 47));
+
+    let template = template(&bc);
+    assert_eq!(template.selected, vec![high, mid, low]);
+    assert_eq!(template.total_fees, 9);
+    assert_eq!(template.reward, 59);
+}
+
+#[test]
+fn child_pays_for_parent() {
+    let alice = Wallet::from_seed("alice");
+    let bob = Wallet::from_seed("bob");
+    let (mut bc, coinbases) = funded_chain(2);
+
+    // Pai paga 1, o filho paga 20: o pacote passa à frente da independente de 5
+    let parent_tx = pay(&alice, &coinbases[0], 0, &bob, 49);
+    let parent = submit(&mut bc, &parent_tx);
+    let independent = submit(&mut bc, &pay(&alice, &coinbases[1], 0, &bob, 45));
+    let child = submit(&mut bc, &pay(&bob, &parent_tx, 0, &bob, 29));
+
+    assert_eq!(template(&bc).selected, vec![parent, child, independent]);
+}
+
+#[test]
+fn siblings_are_rescored_after_their_parent_is_included() {
+    let alice = Wallet::from_seed("alice");
+    let bob = Wallet::from_seed("bob");
+    let (mut bc, coinbases) = funded_chain(2);
+
+    let parent_tx = spend(&alice, &coinbases[0], 0, vec![
+        Output::to_address(40, &address(&alice)),
+        Output::to_address(9, &address(&alice)),
+    ]);
+    let parent = submit(&mut bc, &parent_tx);
+    let rich_child = submit(&mut bc, &pay(&alice, &parent_tx, 0, &bob, 10));
+    let poor_child = submit(&mut bc, &pay(&alice, &parent_tx, 1, &bob, 3));
+    let independent = submit(&mut bc, &pay(&alice, &coinbases[1], 0, &bob, 43));
+
+    // Com o pai já no bloco, o filho de taxa 6 perde para a independente de 7
+    assert_eq!(template(&bc).selected, vec![parent, rich_child, independent, poor_child]);
+}
+
+#[test]
+fn packages_that_do_not_fit_are_skipped() {
+    let alice = Wallet::from_seed("alice");
+    let bob = Wallet::from_seed("bob");
+    let (mut bc, coinbases) = funded_chain(2);
+    let empty_size = template(&bc).size;
+
+    let parent_tx = pay(&alice, &coinbases[0], 0, &bob, 49);
+    let child_tx = pay(&bob, &parent_tx, 0, &bob, 30);
+    let single_tx = pay(&alice, &coinbases[1], 0, &bob, 48);
+    let parent = submit(&mut bc, &parent_tx);
+    submit(&mut bc, &child_tx);
+    let single = submit(&mut bc, &single_tx);
+
+    // Cabe uma transação, não as duas do pacote mais lucrativo
+    bc.params.max_block_size = empty_size + single_tx.serialized_size() * 3 / 2;
+    let template = template(&bc);
+    assert_eq!(template.selected, vec![single]);
+    assert!(template.size <= bc.params.max_block_size);
+
+    // Com espaço para duas, o pacote entra e a avulsa fica para depois
+    bc.params.max_block_size = empty_size + parent_tx.serialized_size() + child_tx.serialized_size();
+    let mut block = bc.create_next_block(&address(&alice));
+    assert_eq!(block.transactions[1].calculate_hash(), parent);
+    block.mine();
+    bc.add_block(block).unwrap();
+    assert_eq!(bc.mempool.len(), 1);
+    assert!(bc.mempool.contains(&single));
+}
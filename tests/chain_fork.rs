@@ -0,0 +1,215 @@
+mod common;
+
+use common::{address, mine, next_block, pay, regtest_chain, remine, utxo_bytes};
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::block::Block;
+use utxo_blockchain::models::blockchain::Blockchain;
+use utxo_blockchain::models::chain_params::ChainParams;
+use utxo_blockchain::models::errors::BlockValidationError;
+use utxo_blockchain::models::transaction::Transaction;
+
+/// Blocos minerados em outra corrente a partir do mesmo gênesis
+fn competing_branch(miner: &Wallet, length: usize) -> Vec<Block> {
+    let mut other = regtest_chain();
+    (0..length)
+        .map(|_| {
+            let block = next_block(&mut other, miner);
+            other.add_block(block.clone()).unwrap();
+            block
+        })
+        .collect()
+}
+
+fn chain_with_blocks(miner: &Wallet, length: usize) -> Blockchain {
+    let mut bc = regtest_chain();
+    for _ in 0..length {
+        mine(&mut bc, miner);
+    }
+    bc
+}
+
+#[test]
+fn lighter_branch_stays_on_the_side() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = chain_with_blocks(&alice, 2);
+    let tip = bc.tip_hash();
+
+    let side = competing_branch(&Wallet::from_seed("bob"), 1);
+    let update = bc.add_block(side[0].clone()).unwrap();
+
+    assert!(update.connected.is_empty() && !update.is_reorg());
+    assert_eq!(bc.tip_hash(), tip);
+    assert!(bc.block_index.contains_key(&side[0].header.calculate_hash()));
+    assert_eq!(bc.side_blocks().len(), 1);
+}
+
+#[test]
+fn heavier_branch_triggers_reorg() {
+    let alice = Wallet::from_seed("alice");
+    let bob = Wallet::from_seed("bob");
+    let mut bc = chain_with_blocks(&alice, 1);
+    let old_tip = bc.tip_hash();
+
+    let branch = competing_branch(&bob, 2);
+    let hashes: Vec<_> = branch.iter().map(|b| b.header.calculate_hash()).collect();
+
+    // Empata no primeiro bloco, passa à frente no segundo
+    assert!(!bc.add_block(branch[0].clone()).unwrap().is_reorg());
+    let update = bc.add_block(branch[1].clone()).unwrap();
+
+    assert_eq!(update.disconnected, vec![old_tip]);
+    assert_eq!(update.connected, hashes);
+    assert_eq!(bc.tip_hash(), hashes[1]);
+    assert_eq!(bc.get_balance(&address(&alice)), 0);
+    assert_eq!(bc.get_balance(&address(&bob)), 100);
+    assert!(bc.side_blocks().iter().any(|b| b.header.calculate_hash() == old_tip));
+}
+
+#[test]
+fn equal_work_keeps_first_seen_tip() {
+    let mut bc = chain_with_blocks(&Wallet::from_seed("alice"), 2);
+    let tip = bc.tip_hash();
+    let work = bc.tip_work();
+
+    for block in competing_branch(&Wallet::from_seed("bob"), 2) {
+        let update = bc.add_block(block).unwrap();
+        assert!(update.connected.is_empty());
+    }
+    assert_eq!(bc.tip_hash(), tip);
+    assert_eq!(bc.tip_work(), work);
+}
+
+#[test]
+fn known_heavier_branch_is_picked_up_again() {
+    let mut bc = chain_with_blocks(&Wallet::from_seed("alice"), 2);
+    let branch = competing_branch(&Wallet::from_seed("bob"), 2);
+    for block in &branch {
+        assert!(bc.add_block(block.clone()).unwrap().connected.is_empty());
+    }
+
+    // Sem o topo, o branch lateral já conhecido passa a ter mais trabalho
+    bc.disconnect_tip().unwrap();
+    let update = bc.add_block(branch[1].clone()).unwrap();
+    let hashes: Vec<_> = branch.iter().map(|b| b.header.calculate_hash()).collect();
+    assert_eq!(update.connected, hashes);
+    assert_eq!(bc.tip_hash(), hashes[1]);
+}
+
+#[test]
+fn orphan_is_rejected_until_parent_arrives() {
+    let mut bc = regtest_chain();
+    let branch = competing_branch(&Wallet::from_seed("bob"), 2);
+
+    let orphan = branch[1].clone();
+    assert_eq!(
+        bc.add_block(orphan.clone()).unwrap_err(),
+        BlockValidationError::UnknownParent { prev_block_hash: branch[0].header.calculate_hash() },
+    );
+    assert!(!bc.block_index.contains_key(&orphan.header.calculate_hash()));
+
+    bc.add_block(branch[0].clone()).unwrap();
+    let update = bc.add_block(orphan.clone()).unwrap();
+    assert_eq!(update.connected, vec![orphan.header.calculate_hash()]);
+    assert_eq!(bc.chain.len(), 3);
+}
+
+#[test]
+fn second_root_is_rejected() {
+    let mut bc = chain_with_blocks(&Wallet::from_seed("alice"), 1);
+
+    // Outro "gênesis" com mais trabalho que a corrente inteira
+    let mut params = ChainParams::regtest();
+    params.genesis.message = "raiz falsa";
+    params.genesis.difficulty = 8;
+    let mut fake = params.genesis_block();
+    fake.mine();
+    assert!(fake.header.work() > bc.tip_work());
+
+    let hash = fake.header.calculate_hash();
+    assert_eq!(bc.add_block(fake).unwrap_err(), BlockValidationError::UnexpectedGenesis { hash });
+    assert!(!bc.block_index.contains_key(&hash));
+}
+
+/// Duas correntes com as mesmas três coinbases maduras de alice
+fn shared_base() -> (Blockchain, Blockchain, Vec<Transaction>) {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    let mut other = regtest_chain();
+    let coinbases = (0..3)
+        .map(|_| {
+            let coinbase = mine(&mut bc, &alice);
+            other.add_block(bc.chain.last().unwrap().clone()).unwrap();
+            coinbase
+        })
+        .collect();
+    (bc, other, coinbases)
+}
+
+#[test]
+fn reorg_resubmits_drops_and_purges() {
+    let alice = Wallet::from_seed("alice");
+    let (bob, carol) = (Wallet::from_seed("bob"), Wallet::from_seed("carol"));
+    let (mut bc, mut other, coinbases) = shared_base();
+
+    // Branch ativo: confirma T1 e D1, com P1 esperando na mempool
+    let t1 = pay(&alice, &coinbases[0], 0, &bob, 49);
+    let d1 = pay(&alice, &coinbases[1], 0, &bob, 49);
+    let p1 = pay(&alice, &coinbases[2], 0, &bob, 49);
+    bc.submit_transaction(t1.clone()).unwrap();
+    bc.submit_transaction(d1.clone()).unwrap();
+    mine(&mut bc, &alice);
+    bc.submit_transaction(p1.clone()).unwrap();
+
+    // Branch concorrente gasta as mesmas coinbases de D1 e P1 para carol
+    other.submit_transaction(pay(&alice, &coinbases[1], 0, &carol, 49)).unwrap();
+    other.submit_transaction(pay(&alice, &coinbases[2], 0, &carol, 49)).unwrap();
+    let b1 = next_block(&mut other, &carol);
+    other.add_block(b1.clone()).unwrap();
+    let b2 = next_block(&mut other, &carol);
+
+    assert!(!bc.add_block(b1).unwrap().is_reorg());
+    let update = bc.add_block(b2).unwrap();
+
+    assert!(update.is_reorg());
+    assert_eq!((update.resubmitted, update.dropped, update.purged), (1, 1, 1));
+    assert!(bc.mempool.contains(&t1.calculate_hash()));
+    assert!(!bc.mempool.contains(&d1.calculate_hash()));
+    assert!(!bc.mempool.contains(&p1.calculate_hash()));
+}
+
+#[test]
+fn failed_reorg_restores_chain_and_mempool() {
+    let alice = Wallet::from_seed("alice");
+    let (bob, carol) = (Wallet::from_seed("bob"), Wallet::from_seed("carol"));
+    let (mut bc, mut other, coinbases) = shared_base();
+
+    mine(&mut bc, &alice);
+    let tip = bc.tip_hash();
+    let t = pay(&alice, &coinbases[0], 0, &bob, 49);
+    let p1 = pay(&alice, &coinbases[2], 0, &bob, 49);
+    bc.submit_transaction(t.clone()).unwrap();
+    bc.submit_transaction(p1.clone()).unwrap();
+    let utxos_before = utxo_bytes(&bc);
+
+    // b1 é válido e tira T e P1 da mempool; b2 paga coinbase demais
+    other.submit_transaction(t.clone()).unwrap();
+    other.submit_transaction(pay(&alice, &coinbases[2], 0, &carol, 49)).unwrap();
+    let b1 = next_block(&mut other, &carol);
+    other.add_block(b1.clone()).unwrap();
+    let mut b2 = next_block(&mut other, &carol);
+    b2.transactions[0].outputs[0].value += 1;
+    remine(&mut b2);
+
+    bc.add_block(b1.clone()).unwrap();
+    assert_eq!(
+        bc.add_block(b2.clone()).unwrap_err(),
+        BlockValidationError::ExcessiveCoinbaseReward { max: 50, found: 51 },
+    );
+
+    assert_eq!(bc.tip_hash(), tip);
+    assert_eq!(utxo_bytes(&bc), utxos_before);
+    assert!(bc.mempool.contains(&t.calculate_hash()));
+    assert!(bc.mempool.contains(&p1.calculate_hash()));
+    assert!(!bc.block_index.contains_key(&b2.header.calculate_hash()));
+    assert!(bc.block_index.contains_key(&b1.header.calculate_hash()));
+}
@@ -0,0 +1,70 @@
+use std::collections::HashSet;
+use utxo_blockchain::crypto::hashing::Hash;
+use utxo_blockchain::models::blockchain::Blockchain;
+use utxo_blockchain::models::chain_params::{ChainParams, Network};
+
+fn all_networks() -> [ChainParams; 3] {
+    [ChainParams::mainnet(), ChainParams::testnet(), ChainParams::regtest()]
+}
+
+#[test]
+fn presets_match_their_names() {
+    for (name, network) in [("mainnet", Network::Mainnet), ("testnet", Network::Testnet), ("regtest", Network::Regtest)] {
+        assert_eq!(ChainParams::from_name(name).unwrap().network, network);
+    }
+    assert!(ChainParams::from_name("signet").is_none());
+}
+
+#[test]
+fn each_network_has_its_own_magic() {
+    let magics: HashSet<[u8; 4]> = all_networks().iter().map(|params| params.magic).collect();
+    assert_eq!(magics.len(), 3);
+
+    // Nem conversa com nós do Bitcoin por engano
+    let bitcoin = [[0xf9, 0xbe, 0xb4, 0xd9], [0x0b, 0x11, 0x09, 0x07], [0xfa, 0xbf, 0xb5, 0xda]];
+    assert!(bitcoin.iter().all(|magic| !magics.contains(magic)));
+}
+
+#[test]
+fn each_network_has_its_own_address_version() {
+    let versions: HashSet<u8> = all_networks().iter().map(|params| params.network.address_version()).collect();
+    assert_eq!(versions.len(), 3);
+
+    // P2PKH e P2SH do Bitcoin, na mainnet e na testnet
+    let bitcoin = [0x00, 0x05, 0x6f, 0xc4];
+    assert!(bitcoin.iter().all(|version| !versions.contains(version)));
+}
+
+#[test]
+fn genesis_matches_the_pinned_hash() {
+    for params in all_networks() {
+        let genesis = params.genesis_block();
+        let hash = genesis.header.calculate_hash();
+        assert_eq!(hash, params.genesis_hash(), "gênesis de {:?} mudou", params.network);
+        assert!(hash.has_sufficient_difficulty(params.genesis.difficulty));
+        assert_eq!(Blockchain::new(params).tip_hash(), hash);
+    }
+
+    let hashes: HashSet<Hash> = all_networks().iter().map(|params| params.genesis_hash()).collect();
+    assert_eq!(hashes.len(), 3);
+}
+
+#[test]
+#[should_panic(expected = "Gênesis construído difere do fixado")]
+fn changed_genesis_is_caught_at_startup() {
+    let mut params = ChainParams::regtest();
+    params.genesis.message = "outro gênesis";
+    Blockchain::new(params);
+}
+
+#[test]
+fn file_from_another_network_is_rejected() {
+    let path = std::env::temp_dir().join(format!("chain_params_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    Blockchain::new(ChainParams::regtest()).save_to_file(path).unwrap();
+
+    let error = Blockchain::load_from_file(path, ChainParams::testnet()).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(Blockchain::load_from_file(path, ChainParams::regtest()).is_ok());
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{mine, next_block, pay, regtest_chain, utxo_bytes};
+use utxo_blockchain::crypto::wallet::Wallet;
+
+#[test]
+fn disconnect_restores_utxo_set_exactly() {
+    let alice = Wallet::from_seed("alice");
+    let bob = Wallet::from_seed("bob");
+    let mut bc = regtest_chain();
+    let coinbase = mine(&mut bc, &alice);
+
+    let before = utxo_bytes(&bc);
+    let tip = bc.tip_hash();
+
+    // Pai e filho no mesmo bloco: o output intermediário nasce e morre dentro dele
+    let parent = pay(&alice, &coinbase, 0, &bob, 49);
+    let child = pay(&bob, &parent, 0, &alice, 48);
+    bc.submit_transaction(parent.clone()).unwrap();
+    bc.submit_transaction(child.clone()).unwrap();
+    mine(&mut bc, &bob);
+    assert_eq!(bc.chain.last().unwrap().transactions.len(), 3);
+    assert_ne!(utxo_bytes(&bc), before);
+
+    let block = bc.disconnect_tip().unwrap();
+    assert_eq!(block.transactions[1].calculate_hash(), parent.calculate_hash());
+    assert_eq!(bc.tip_hash(), tip);
+    assert_eq!(utxo_bytes(&bc), before);
+    assert_eq!(bc.undo.len(), bc.chain.len());
+}
+
+#[test]
+fn disconnecting_every_block_leaves_only_genesis() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    let genesis = utxo_bytes(&bc);
+
+    let coinbase = mine(&mut bc, &alice);
+    bc.submit_transaction(pay(&alice, &coinbase, 0, &alice, 40)).unwrap();
+    mine(&mut bc, &alice);
+    mine(&mut bc, &alice);
+
+    while bc.chain.len() > 1 {
+        bc.disconnect_tip().unwrap();
+    }
+    assert_eq!(utxo_bytes(&bc), genesis);
+}
+
+#[test]
+fn disconnect_returns_block_transactions_to_mempool() {
+    let alice = Wallet::from_seed("alice");
+    let bob = Wallet::from_seed("bob");
+    let mut bc = regtest_chain();
+    let coinbase = mine(&mut bc, &alice);
+
+    let tx = pay(&alice, &coinbase, 0, &bob, 49);
+    bc.submit_transaction(tx.clone()).unwrap();
+    let block = next_block(&mut bc, &bob);
+    let reward = block.transactions[0].clone();
+    bc.add_block(block).unwrap();
+    assert!(bc.mempool.is_empty());
+
+    // Gasta a coinbase do topo: deixa de valer quando ele sai
+    let spends_reward = pay(&bob, &reward, 0, &bob, 49);
+    bc.submit_transaction(spends_reward.clone()).unwrap();
+
+    bc.disconnect_tip().unwrap();
+    assert!(bc.mempool.contains(&tx.calculate_hash()));
+    assert!(!bc.mempool.contains(&spends_reward.calculate_hash()));
+    assert_eq!(bc.mempool.len(), 1);
+}
+
+#[test]
+fn known_block_reconnects_after_disconnect() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    mine(&mut bc, &alice);
+    mine(&mut bc, &alice);
+    let tip = bc.tip_hash();
+    let before = utxo_bytes(&bc);
+
+    let block = bc.disconnect_tip().unwrap();
+    assert_ne!(bc.tip_hash(), tip);
+
+    // O bloco continua na árvore e tem mais trabalho que o topo atual
+    let update = bc.add_block(block.clone()).unwrap();
+    assert_eq!(update.connected, vec![tip]);
+    assert_eq!(bc.tip_hash(), tip);
+    assert_eq!(utxo_bytes(&bc), before);
+
+    // Já no branch ativo, reenviar não muda nada
+    assert_eq!(bc.add_block(block).unwrap(), Default::default());
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::{address, mine, next_block, pay, regtest_params, remine};
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::block::Block;
+use utxo_blockchain::models::blockchain::Blockchain;
+use utxo_blockchain::models::errors::{BlockValidationError, TxValidationError};
+use utxo_blockchain::models::transaction::{Transaction, MAX_COINBASE_EXTRA};
+
+/// Próximo bloco com a coinbase trocada
+fn with_coinbase(bc: &mut Blockchain, coinbase: Transaction) -> Block {
+    let mut block = next_block(bc, &Wallet::from_seed("miner"));
+    block.transactions[0] = coinbase;
+    remine(&mut block);
+    block
+}
+
+#[test]
+fn coinbase_spend_waits_for_maturity() {
+    let alice = Wallet::from_seed("alice");
+    let mut params = regtest_params();
+    params.coinbase_maturity = 3;
+    let mut bc = Blockchain::new(params);
+    let coinbase = mine(&mut bc, &alice);
+    let tx = pay(&alice, &coinbase, 0, &alice, 49);
+
+    // Criada na altura 1; o próximo bloco (altura 2) daria 1 confirmação
+    assert_eq!(
+        bc.submit_transaction(tx.clone()).unwrap_err(),
+        TxValidationError::ImmatureCoinbase { input_index: 0, confirmations: 1, required: 3 },
+    );
+
+    mine(&mut bc, &alice);
+    let mut block = next_block(&mut bc, &alice);
+    block.transactions.push(tx.clone());
+    remine(&mut block);
+    assert_eq!(
+        bc.add_block(block).unwrap_err(),
+        BlockValidationError::InvalidTransaction {
+            tx_hash: tx.calculate_hash(),
+            error: TxValidationError::ImmatureCoinbase { input_index: 0, confirmations: 2, required: 3 },
+        },
+    );
+
+    mine(&mut bc, &alice);
+    bc.submit_transaction(tx).unwrap();
+}
+
+#[test]
+fn identical_rewards_get_distinct_hashes() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = Blockchain::new(regtest_params());
+    let first = mine(&mut bc, &alice);
+    let second = mine(&mut bc, &alice);
+
+    assert_eq!(first.outputs[0].value, second.outputs[0].value);
+    assert_ne!(first.calculate_hash(), second.calculate_hash());
+    assert_eq!((first.coinbase_height(), second.coinbase_height()), (Some(1), Some(2)));
+    assert_eq!(bc.get_balance(&address(&alice)), 100);
+}
+
+#[test]
+fn coinbase_must_commit_to_block_height() {
+    let mut bc = Blockchain::new(regtest_params());
+    let miner = address(&Wallet::from_seed("miner"));
+
+    let block = with_coinbase(&mut bc, Transaction::coinbase(&miner, 50, 2));
+    assert_eq!(
+        bc.add_block(block).unwrap_err(),
+        BlockValidationError::WrongCoinbaseHeight { expected: 1, found: Some(2) },
+    );
+
+    let block = with_coinbase(&mut bc, Transaction::coinbase(&miner, 50, 1));
+    bc.add_block(block).unwrap();
+}
+
+#[test]
+fn coinbase_extra_is_limited() {
+    let mut bc = Blockchain::new(regtest_params());
+    let miner = address(&Wallet::from_seed("miner"));
+
+    let oversized = Transaction::coinbase_with_extra(&miner, 50, 1, vec![7; MAX_COINBASE_EXTRA + 1]);
+    let block = with_coinbase(&mut bc, oversized);
+    assert_eq!(
+        bc.add_block(block).unwrap_err(),
+        BlockValidationError::CoinbaseExtraTooLarge { size: MAX_COINBASE_EXTRA + 1 },
+    );
+
+    let at_limit = Transaction::coinbase_with_extra(&miner, 50, 1, vec![7; MAX_COINBASE_EXTRA]);
+    let block = with_coinbase(&mut bc, at_limit);
+    bc.add_block(block).unwrap();
+}
+
+#[test]
+fn only_the_first_transaction_may_be_a_coinbase() {
+    let mut bc = Blockchain::new(regtest_params());
+    let miner = address(&Wallet::from_seed("miner"));
+
+    let mut block = next_block(&mut bc, &Wallet::from_seed("miner"));
+    block.transactions.push(Transaction::coinbase_with_extra(&miner, 0, 1, b"outra".to_vec()));
+    remine(&mut block);
+    assert_eq!(bc.add_block(block).unwrap_err(), BlockValidationError::MultipleCoinbase { index: 1 });
+
+    let mut block = next_block(&mut bc, &Wallet::from_seed("miner"));
+    block.transactions.remove(0);
+    remine(&mut block);
+    assert_eq!(bc.add_block(block).unwrap_err(), BlockValidationError::MissingCoinbase);
+}
@@ -0,0 +1,121 @@
+//! Utilitários compartilhados pelos testes de integração
+#![allow(dead_code)]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+use utxo_blockchain::crypto::address::Address;
+use utxo_blockchain::crypto::hashing::Hash;
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::block::Block;
+use utxo_blockchain::models::blockchain::Blockchain;
+use utxo_blockchain::models::chain_params::{ChainParams, Network};
+use utxo_blockchain::models::errors::{ScriptError, TxValidationError};
+use utxo_blockchain::models::transaction::{Input, Output, Transaction};
+
+/// Regtest com coinbases gastáveis já no bloco seguinte
+pub fn regtest_params() -> ChainParams {
+    let mut params = ChainParams::regtest();
+    params.coinbase_maturity = 1;
+    params
+}
+
+pub fn regtest_chain() -> Blockchain {
+    Blockchain::new(regtest_params())
+}
+
+/// Corrente com uma coinbase madura de alice, criada na altura 1
+pub fn funded_chain() -> (Blockchain, Transaction) {
+    let mut bc = regtest_chain();
+    let coinbase = mine(&mut bc, &Wallet::from_seed("alice"));
+    (bc, coinbase)
+}
+
+pub fn address(wallet: &Wallet) -> Address {
+    wallet.address(Network::Regtest)
+}
+
+/// Relógio manual instalado numa corrente
+#[derive(Clone)]
+pub struct TestClock(Arc<AtomicI64>);
+
+impl TestClock {
+    pub fn install(bc: &mut Blockchain, start: i64) -> Self {
+        let clock = TestClock(Arc::new(AtomicI64::new(start)));
+        let time = clock.0.clone();
+        bc.clock = Arc::new(move || time.load(Ordering::SeqCst));
+        clock
+    }
+
+    pub fn now(&self) -> i64 {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn set(&self, time: i64) {
+        self.0.store(time, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, seconds: i64) {
+        self.0.fetch_add(seconds, Ordering::SeqCst);
+    }
+}
+
+/// Próximo bloco minerado sobre o topo, sem conectar
+pub fn next_block(bc: &mut Blockchain, miner: &Wallet) -> Block {
+    let mut block = bc.create_next_block(&address(miner));
+    block.mine();
+    block
+}
+
+/// Minera e conecta um bloco, retornando a coinbase dele
+pub fn mine(bc: &mut Blockchain, miner: &Wallet) -> Transaction {
+    let block = next_block(bc, miner);
+    let coinbase = block.transactions[0].clone();
+    bc.add_block(block).expect("bloco minerado localmente é válido");
+    coinbase
+}
+
+/// Refaz a merkle root e a prova de trabalho depois de mexer no bloco
+pub fn remine(block: &mut Block) {
+    block.header.merkle_root = Block::new(block.header.prev_block_hash, block.transactions.clone(), 0).header.merkle_root;
+    block.header.nonce = 0;
+    block.mine();
+}
+
+/// Gasta o output `output_index` de `prev` (pago ao endereço de `owner`)
+pub fn spend(owner: &Wallet, prev: &Transaction, output_index: usize, outputs: Vec<Output>) -> Transaction {
+    spend_input(owner, prev, Input::new(prev.calculate_hash(), output_index), outputs)
+}
+
+/// Como `spend`, mas com um input já montado (sequence, sighash...)
+pub fn spend_input(owner: &Wallet, prev: &Transaction, input: Input, outputs: Vec<Output>) -> Transaction {
+    let spent = prev.outputs[input.output_index].clone();
+    let mut tx = Transaction::new(vec![input], outputs);
+    assert!(tx.sign_input(0, &spent, &owner.secret));
+    tx
+}
+
+/// Paga `value` ao endereço de `to`, gastando o output `output_index` de `prev`
+pub fn pay(owner: &Wallet, prev: &Transaction, output_index: usize, to: &Wallet, value: u64) -> Transaction {
+    spend(owner, prev, output_index, vec![Output::to_address(value, &address(to))])
+}
+
+/// Transação só para testar a estrutura da mempool (não precisa ser válida)
+pub fn dummy_tx(seed: &[u8], outputs: usize) -> Transaction {
+    let owner = address(&Wallet::from_seed("dummy"));
+    Transaction::new(vec![Input::new(Hash::sha256(seed), 0)], vec![Output::to_address(1, &owner); outputs])
+}
+
+/// Erro de script do input 0, ou pânico se a falha foi outra
+pub fn script_error(result: Result<impl std::fmt::Debug, TxValidationError>) -> ScriptError {
+    match result {
+        Err(TxValidationError::InvalidScript { input_index: 0, error }) => error,
+        other => panic!("esperava falha de script, veio {:?}", other),
+    }
+}
+
+/// UTXO set serializado em ordem estável, para comparar byte a byte
+pub fn utxo_bytes(bc: &Blockchain) -> Vec<u8> {
+    let mut utxos: Vec<_> = bc.utxos.iter().collect();
+    utxos.sort_by_key(|(key, _)| (key.tx_hash, key.output_index));
+    bincode::serialize(&utxos).unwrap()
+}
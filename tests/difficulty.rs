@@ -0,0 +1,146 @@
+mod common;
+
+use common::{address, next_block, regtest_params, remine, TestClock};
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::block::Block;
+use utxo_blockchain::models::blockchain::Blockchain;
+use utxo_blockchain::models::difficulty::RetargetParams;
+use utxo_blockchain::models::errors::BlockValidationError;
+use utxo_blockchain::models::transaction::Transaction;
+
+const RETARGET: RetargetParams = RetargetParams {
+    window: 4,
+    target_block_time: 600,
+    max_adjustment: 2,
+    min_difficulty: 1,
+    max_difficulty: 6,
+};
+
+/// Janela de 4 blocos: espera 3 intervalos, 1800 segundos
+fn expected_timespan() -> i64 {
+    RETARGET.target_block_time * (RETARGET.window as i64 - 1)
+}
+
+#[test]
+fn on_target_keeps_difficulty() {
+    assert_eq!(RETARGET.retarget(3, expected_timespan()), 3);
+    // Dentro do dobro/metade do esperado ainda não muda
+    assert_eq!(RETARGET.retarget(3, expected_timespan() * 2 - 1), 3);
+    assert_eq!(RETARGET.retarget(3, expected_timespan() / 2 + 1), 3);
+}
+
+#[test]
+fn adjustment_is_clamped_in_both_directions() {
+    // 18x mais rápido pediria +4 bits, mas o máximo por ajuste é 2
+    assert_eq!(RETARGET.retarget(3, 100), 5);
+    assert_eq!(RETARGET.retarget(3, expected_timespan() / 2), 4);
+    // 50x mais lento pediria -5 bits
+    assert_eq!(RETARGET.retarget(4, expected_timespan() * 50), 2);
+    assert_eq!(RETARGET.retarget(4, expected_timespan() * 2), 3);
+}
+
+#[test]
+fn result_stays_within_min_and_max() {
+    assert_eq!(RETARGET.retarget(1, expected_timespan() * 50), RETARGET.min_difficulty);
+    assert_eq!(RETARGET.retarget(6, 1), RETARGET.max_difficulty);
+    // Timestamps fora de ordem contam como o bloco mais rápido possível
+    assert_eq!(RETARGET.retarget(5, -100), RETARGET.max_difficulty);
+}
+
+/// Corrente regtest com o retarget de teste e blocos a cada `spacing` segundos
+fn retarget_chain() -> (Blockchain, TestClock) {
+    let mut params = regtest_params();
+    params.retarget = RetargetParams { min_difficulty: 0, ..RETARGET };
+    let mut bc = Blockchain::new(params);
+    let genesis_time = bc.chain[0].header.timestamp;
+    let clock = TestClock::install(&mut bc, genesis_time);
+    (bc, clock)
+}
+
+fn mine_spaced(bc: &mut Blockchain, clock: &TestClock, spacing: i64) {
+    clock.advance(spacing);
+    let block = next_block(bc, &Wallet::from_seed("miner"));
+    bc.add_block(block).unwrap();
+}
+
+#[test]
+fn retarget_only_at_window_boundary() {
+    let (mut bc, clock) = retarget_chain();
+
+    // Alturas 1 a 3 herdam a dificuldade do topo, mesmo com blocos rápidos
+    for _ in 0..3 {
+        assert_eq!(bc.next_difficulty(), 0);
+        mine_spaced(&mut bc, &clock, 60);
+    }
+
+    // Altura 4 fecha a janela: 180s em vez de 1800s sobe 2 bits (limite do ajuste)
+    assert_eq!(bc.next_difficulty(), 2);
+    mine_spaced(&mut bc, &clock, 60);
+    assert_eq!(bc.chain.last().unwrap().header.difficulty, 2);
+
+    // Altura 5 não é múltipla da janela
+    assert_eq!(bc.next_difficulty(), 2);
+}
+
+#[test]
+fn slow_window_lowers_difficulty_to_minimum() {
+    let (mut bc, clock) = retarget_chain();
+    for _ in 0..3 {
+        mine_spaced(&mut bc, &clock, 60);
+    }
+    mine_spaced(&mut bc, &clock, 60);
+    assert_eq!(bc.next_difficulty(), 2);
+
+    // Janela seguinte (alturas 4 a 7) muito lenta
+    for _ in 0..3 {
+        mine_spaced(&mut bc, &clock, 6000);
+    }
+    assert_eq!(bc.chain.len(), 8);
+    assert_eq!(bc.next_difficulty(), 0);
+}
+
+#[test]
+fn block_with_wrong_difficulty_is_rejected() {
+    let (mut bc, clock) = retarget_chain();
+    for _ in 0..3 {
+        mine_spaced(&mut bc, &clock, 60);
+    }
+    clock.advance(60);
+    let mut block = next_block(&mut bc, &Wallet::from_seed("miner"));
+    block.header.difficulty = 0;
+    remine(&mut block);
+
+    assert_eq!(
+        bc.add_block(block).unwrap_err(),
+        BlockValidationError::WrongDifficulty { expected: 2, found: 0 },
+    );
+}
+
+#[test]
+fn side_block_must_follow_retarget_of_its_branch() {
+    let (mut bc, clock) = retarget_chain();
+    for _ in 0..4 {
+        mine_spaced(&mut bc, &clock, 60);
+    }
+    assert_eq!(bc.chain[4].header.difficulty, 2);
+
+    // Irmão do topo declarando dificuldade 0: não custaria trabalho nenhum
+    let parent = bc.chain[3].header.calculate_hash();
+    let miner = address(&Wallet::from_seed("outro minerador"));
+    let mut side = Block::new(parent, vec![Transaction::coinbase(&miner, 50, 4)], 0);
+    side.header.timestamp = clock.now();
+    remine(&mut side);
+    assert_eq!(bc.add_block(side.clone()).unwrap_err(), BlockValidationError::WrongDifficulty { expected: 2, found: 0 });
+
+    // Com a dificuldade certa, o timestamp ainda segue o median-time-past do branch
+    side.header.difficulty = 2;
+    side.header.timestamp = bc.median_time_past_after(&parent).unwrap();
+    remine(&mut side);
+    assert!(matches!(bc.add_block(side.clone()), Err(BlockValidationError::TimestampTooOld { .. })));
+
+    assert!(bc.side_blocks().is_empty());
+    side.header.timestamp = clock.now();
+    remine(&mut side);
+    bc.add_block(side).unwrap();
+    assert_eq!(bc.side_blocks().len(), 1);
+}
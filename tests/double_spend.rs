@@ -0,0 +1,116 @@
+mod common;
+
+use common::{address, mine, next_block, pay, regtest_chain, remine};
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::blockchain::UTXOKey;
+use utxo_blockchain::models::errors::{BlockValidationError, TxValidationError};
+use utxo_blockchain::models::transaction::{Input, Output, Transaction};
+
+#[test]
+fn block_may_spend_output_created_earlier_in_it() {
+    let alice = Wallet::from_seed("alice");
+    let bob = Wallet::from_seed("bob");
+    let mut bc = regtest_chain();
+    let coinbase = mine(&mut bc, &alice);
+
+    let parent = pay(&alice, &coinbase, 0, &bob, 49);
+    let child = pay(&bob, &parent, 0, &bob, 48);
+    let mut block = next_block(&mut bc, &alice);
+    block.transactions.extend([parent, child.clone()]);
+    remine(&mut block);
+
+    bc.add_block(block).unwrap();
+    assert_eq!(bc.get_balance(&address(&bob)), 48);
+    assert!(bc.utxos.contains_key(&UTXOKey { tx_hash: child.calculate_hash(), output_index: 0 }));
+}
+
+#[test]
+fn block_rejects_child_before_parent() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    let coinbase = mine(&mut bc, &alice);
+
+    let parent = pay(&alice, &coinbase, 0, &alice, 49);
+    let child = pay(&alice, &parent, 0, &alice, 48);
+    let mut block = next_block(&mut bc, &alice);
+    block.transactions.extend([child.clone(), parent.clone()]);
+    remine(&mut block);
+
+    assert_eq!(
+        bc.add_block(block).unwrap_err(),
+        BlockValidationError::InvalidTransaction {
+            tx_hash: child.calculate_hash(),
+            error: TxValidationError::MissingInput {
+                input_index: 0,
+                outpoint: UTXOKey { tx_hash: parent.calculate_hash(), output_index: 0 },
+            },
+        },
+    );
+}
+
+#[test]
+fn block_rejects_two_spends_of_same_output() {
+    let alice = Wallet::from_seed("alice");
+    let bob = Wallet::from_seed("bob");
+    let mut bc = regtest_chain();
+    let coinbase = mine(&mut bc, &alice);
+    let tip = bc.tip_hash();
+
+    let first = pay(&alice, &coinbase, 0, &alice, 49);
+    let second = pay(&alice, &coinbase, 0, &bob, 49);
+    let mut block = next_block(&mut bc, &alice);
+    block.transactions.extend([first, second.clone()]);
+    remine(&mut block);
+
+    assert_eq!(
+        bc.add_block(block).unwrap_err(),
+        BlockValidationError::InvalidTransaction {
+            tx_hash: second.calculate_hash(),
+            error: TxValidationError::MissingInput {
+                input_index: 0,
+                outpoint: UTXOKey { tx_hash: coinbase.calculate_hash(), output_index: 0 },
+            },
+        },
+    );
+    assert_eq!(bc.tip_hash(), tip);
+    assert_eq!(bc.get_balance(&address(&alice)), 50);
+}
+
+#[test]
+fn transaction_cannot_spend_same_output_twice() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    let coinbase = mine(&mut bc, &alice);
+
+    let input = Input::new(coinbase.calculate_hash(), 0);
+    let mut tx = Transaction::new(vec![input.clone(), input], vec![Output::to_address(90, &address(&alice))]);
+    for input_index in 0..2 {
+        assert!(tx.sign_input(input_index, &coinbase.outputs[0], &alice.secret));
+    }
+
+    assert_eq!(
+        bc.submit_transaction(tx).unwrap_err(),
+        TxValidationError::DuplicateInput { input_index: 1 },
+    );
+    assert!(bc.mempool.is_empty());
+}
+
+#[test]
+fn mempool_rejects_conflicting_spend() {
+    let alice = Wallet::from_seed("alice");
+    let bob = Wallet::from_seed("bob");
+    let mut bc = regtest_chain();
+    let coinbase = mine(&mut bc, &alice);
+
+    // Inputs com sequence final: nenhuma das duas aceita substituição
+    let first = pay(&alice, &coinbase, 0, &alice, 49);
+    let second = pay(&alice, &coinbase, 0, &bob, 40);
+    bc.submit_transaction(first.clone()).unwrap();
+
+    assert_eq!(
+        bc.submit_transaction(second).unwrap_err(),
+        TxValidationError::MempoolConflict { input_index: 0, conflicting_tx: first.calculate_hash() },
+    );
+    assert_eq!(bc.mempool.len(), 1);
+    assert!(bc.mempool.contains(&first.calculate_hash()));
+}
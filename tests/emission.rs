@@ -0,0 +1,88 @@
+mod common;
+
+use common::{address, mine, next_block, regtest_chain, remine};
+use utxo_blockchain::crypto::hashing::Hash;
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::blockchain::{UTXOKey, UtxoEntry};
+use utxo_blockchain::models::chain_params::ChainParams;
+use utxo_blockchain::models::emission::EmissionSchedule;
+use utxo_blockchain::models::errors::BlockValidationError;
+use utxo_blockchain::models::transaction::Transaction;
+
+#[test]
+fn subsidy_halves_at_each_interval() {
+    let emission = ChainParams::mainnet().emission;
+    assert_eq!(emission.subsidy_at(0), 50);
+    assert_eq!(emission.subsidy_at(209_999), 50);
+    assert_eq!(emission.subsidy_at(210_000), 25);
+    assert_eq!(emission.subsidy_at(420_000), 12);
+    assert_eq!(emission.subsidy_at(210_000 * 6 - 1), 1);
+    assert_eq!(emission.subsidy_at(210_000 * 6), 0);
+    assert_eq!(emission.subsidy_at(u64::MAX), 0);
+}
+
+#[test]
+fn capped_schedule_has_max_supply() {
+    let emission = ChainParams::mainnet().emission;
+    assert_eq!(emission.issued_until(0), 50);
+    assert_eq!(emission.issued_until(209_999), 50 * 210_000);
+    // 50 + 25 + 12 + 6 + 3 + 1 por era
+    assert_eq!(emission.max_supply(), Some(97 * 210_000));
+    assert_eq!(emission.issued_until(u64::MAX), 97 * 210_000);
+}
+
+#[test]
+fn tail_emission_never_ends() {
+    let emission = EmissionSchedule { initial_subsidy: 50, halving_interval: 10, tail_emission: 3 };
+    assert_eq!(emission.subsidy_at(30), 6);
+    assert_eq!(emission.subsidy_at(40), 3);
+    assert_eq!(emission.subsidy_at(u64::MAX), 3);
+    assert_eq!(emission.max_supply(), None);
+    assert_eq!(emission.issued_until(49), 10 * (50 + 25 + 12 + 6 + 3));
+
+    let flat = EmissionSchedule { initial_subsidy: 50, halving_interval: 0, tail_emission: 0 };
+    assert_eq!(flat.subsidy_at(1_000_000), 50);
+    assert_eq!(flat.issued_until(9), 500);
+}
+
+#[test]
+fn block_reward_follows_the_schedule() {
+    let miner = Wallet::from_seed("miner");
+    let mut bc = regtest_chain();
+    let interval = bc.params.emission.halving_interval;
+    while (bc.chain.len() as u64) < interval {
+        mine(&mut bc, &miner);
+    }
+    assert!(bc.supply_is_consistent());
+
+    // A altura `interval` já paga metade
+    let height = bc.chain.len() as u64;
+    assert_eq!(bc.subsidy_at(height), 25);
+    let mut block = next_block(&mut bc, &miner);
+    block.transactions[0] = Transaction::coinbase(&address(&miner), 50, height);
+    remine(&mut block);
+    assert_eq!(
+        bc.add_block(block).unwrap_err(),
+        BlockValidationError::ExcessiveCoinbaseReward { max: 25, found: 50 },
+    );
+
+    let coinbase = mine(&mut bc, &miner);
+    assert_eq!(coinbase.outputs[0].value, 25);
+    assert_eq!(bc.circulating_supply(), Some(bc.params.emission.issued_until(height)));
+    assert!(bc.supply_is_consistent());
+}
+
+#[test]
+fn overflowing_supply_is_inconsistent() {
+    let mut bc = regtest_chain();
+    let genesis_coinbase = bc.chain[0].transactions[0].clone();
+    let mut output = genesis_coinbase.outputs[0].clone();
+    output.value = u64::MAX;
+    bc.utxos.insert(
+        UTXOKey { tx_hash: Hash::sha256(b"forjado"), output_index: 0 },
+        UtxoEntry { output, height: 0, is_coinbase: false },
+    );
+
+    assert_eq!(bc.circulating_supply(), None);
+    assert!(!bc.supply_is_consistent());
+}
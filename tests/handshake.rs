@@ -0,0 +1,77 @@
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+use utxo_blockchain::crypto::hashing::Hash;
+use utxo_blockchain::models::blockchain::Blockchain;
+use utxo_blockchain::models::chain_params::ChainParams;
+use utxo_blockchain::network::messages::{handshake, read_message, write_message, HandshakeError, Message, VersionInfo};
+use utxo_blockchain::network::server::Node;
+
+/// Sobe um nó regtest numa porta livre e retorna o endereço e a apresentação dele
+async fn start_node() -> (String, VersionInfo) {
+    let bc = Blockchain::new(ChainParams::regtest());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let node = Node::new(address.clone(), &bc);
+    let version = node.version.clone();
+    tokio::spawn(async move { node.serve(listener).await });
+    (address, version)
+}
+
+/// Manda `first` e confirma que o nó se apresenta e depois fecha a conexão
+async fn assert_dropped_after(first: Message) {
+    let (address, node_version) = start_node().await;
+    let mut stream = TcpStream::connect(&address).await.unwrap();
+    write_message(&mut stream, &first).await.unwrap();
+
+    match read_message(&mut stream).await.unwrap() {
+        Message::Version(theirs) => assert_eq!(theirs.genesis_hash, node_version.genesis_hash),
+        other => panic!("esperava Version, veio {:?}", other),
+    }
+    let next = timeout(Duration::from_secs(5), read_message(&mut stream)).await.expect("nó não fechou a conexão");
+    assert_eq!(next.unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[tokio::test]
+async fn peer_with_other_genesis_is_dropped() {
+    let bc = Blockchain::new(ChainParams::regtest());
+    let version = VersionInfo { genesis_hash: Hash::sha256(b"outro genesis"), ..VersionInfo::from_chain(&bc) };
+    assert!(matches!(version.check(&bc.params), Err(HandshakeError::GenesisMismatch { .. })));
+    assert_dropped_after(Message::Version(version)).await;
+}
+
+#[tokio::test]
+async fn peer_from_other_network_is_dropped() {
+    let version = VersionInfo::from_chain(&Blockchain::new(ChainParams::testnet()));
+    assert_dropped_after(Message::Version(version)).await;
+}
+
+#[tokio::test]
+async fn peer_must_start_with_version() {
+    assert_dropped_after(Message::RequestChain).await;
+}
+
+#[tokio::test]
+async fn matching_peer_stays_connected() {
+    let (address, node_version) = start_node().await;
+    let bc = Blockchain::new(ChainParams::regtest());
+    let mut stream = TcpStream::connect(&address).await.unwrap();
+
+    let theirs = handshake(&mut stream, &VersionInfo::from_chain(&bc), &bc.params).await.unwrap();
+    assert_eq!(theirs.genesis_hash, node_version.genesis_hash);
+    assert_eq!(theirs.height, 1);
+
+    // Depois do handshake o nó fica ouvindo, sem derrubar a conexão
+    write_message(&mut stream, &Message::RequestChain).await.unwrap();
+    assert!(timeout(Duration::from_millis(200), read_message(&mut stream)).await.is_err());
+}
+
+#[tokio::test]
+async fn client_rejects_node_from_other_network() {
+    let (address, _) = start_node().await;
+    let bc = Blockchain::new(ChainParams::testnet());
+    let mut stream = TcpStream::connect(&address).await.unwrap();
+
+    let result = handshake(&mut stream, &VersionInfo::from_chain(&bc), &bc.params).await;
+    assert!(matches!(result, Err(HandshakeError::WrongNetwork { .. })));
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use common::{address, mine, regtest_chain, script_error};
+use utxo_blockchain::crypto::address::Address;
+use utxo_blockchain::crypto::hashing::Hash;
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::blockchain::Blockchain;
+use utxo_blockchain::models::errors::{ScriptError, TxValidationError};
+use utxo_blockchain::models::script::{Op, Script};
+use utxo_blockchain::models::transaction::Output;
+
+const PREIMAGE: &[u8] = b"segredo do contrato";
+const FEE: u64 = 2;
+
+/// Corrente regtest com um HTLC confirmado: `funder` trava, `payee` resgata
+struct Setup {
+    bc: Blockchain,
+    funder: Wallet,
+    payee: Wallet,
+    htlc_hash: Hash,
+    htlc_output: Output,
+    timeout: u64,
+    to: Address,                        // Destino dos resgates e reembolsos
+}
+
+/// Altura do bloco em que uma transação submetida agora entraria
+fn next_height(bc: &Blockchain) -> u64 {
+    bc.chain.len() as u64
+}
+
+fn setup() -> Setup {
+    let mut bc = regtest_chain();
+    let funder = Wallet::from_seed("funder");
+    let payee = Wallet::from_seed("payee");
+
+    let coinbase = mine(&mut bc, &funder);
+    let spent = &coinbase.outputs[0];
+    let timeout = next_height(&bc) + 5;
+    let terms = funder.htlc_terms(&payee.public, Hash::sha256(PREIMAGE), timeout);
+    let fund = funder.fund_htlc(coinbase.calculate_hash(), 0, spent, &terms, spent.value / 2, FEE)
+        .expect("coinbase é da carteira e cobre o valor");
+    let htlc_hash = fund.calculate_hash();
+    let htlc_output = fund.outputs[0].clone();
+    bc.submit_transaction(fund).expect("financiamento do HTLC é válido");
+    mine(&mut bc, &funder);
+
+    let to = address(&Wallet::from_seed("destino"));
+    Setup { bc, funder, payee, htlc_hash, htlc_output, timeout, to }
+}
+
+#[test]
+fn payee_claims_with_preimage() {
+    let mut s = setup();
+    let claim = s.payee.claim_htlc(s.htlc_hash, 0, &s.htlc_output, PREIMAGE, &s.to, FEE).unwrap();
+    let claim_hash = claim.calculate_hash();
+    s.bc.submit_transaction(claim).unwrap();
+
+    let block = s.bc.create_next_block(&address(&s.payee));
+    assert!(block.transactions.iter().any(|tx| tx.calculate_hash() == claim_hash));
+}
+
+#[test]
+fn claim_with_wrong_preimage_fails() {
+    let mut s = setup();
+    assert!(s.payee.claim_htlc(s.htlc_hash, 0, &s.htlc_output, b"outro segredo", &s.to, FEE).is_none());
+
+    // Monta na mão para passar pela carteira e chegar no script
+    let mut claim = s.payee.claim_htlc(s.htlc_hash, 0, &s.htlc_output, PREIMAGE, &s.to, FEE).unwrap();
+    let Op::PushData(signature) = claim.inputs[0].script_sig.0[0].clone() else { unreachable!() };
+    claim.inputs[0].script_sig = Script::new(vec![
+        Op::PushData(signature),
+        Op::PushData(b"outro segredo".to_vec()),
+        Op::PushInt(1),
+    ]);
+    assert_eq!(script_error(s.bc.submit_transaction(claim)), ScriptError::EqualVerifyFailed);
+}
+
+#[test]
+fn refund_key_cannot_take_claim_branch() {
+    let mut s = setup();
+    assert!(s.funder.claim_htlc(s.htlc_hash, 0, &s.htlc_output, PREIMAGE, &s.to, FEE).is_none());
+
+    let mut claim = s.payee.claim_htlc(s.htlc_hash, 0, &s.htlc_output, PREIMAGE, &s.to, FEE).unwrap();
+    let signature = claim.signature_for(0, &s.htlc_output, &s.funder.secret).unwrap();
+    claim.inputs[0].script_sig.0[0] = Op::PushData(signature);
+    assert_eq!(script_error(s.bc.submit_transaction(claim)), ScriptError::EvalFalse);
+}
+
+#[test]
+fn refund_only_after_timeout_height() {
+    let mut s = setup();
+    while next_height(&s.bc) < s.timeout {
+        mine(&mut s.bc, &s.funder);
+    }
+
+    // O reembolso leva lock_time = prazo: no bloco do prazo ainda não vale
+    let refund = s.funder.refund_htlc(s.htlc_hash, 0, &s.htlc_output, &s.to, FEE).unwrap();
+    assert_eq!(refund.lock_time, s.timeout);
+    assert_eq!(
+        s.bc.submit_transaction(refund.clone()).unwrap_err(),
+        TxValidationError::LockTimeNotReached { lock_time: s.timeout },
+    );
+
+    // Antecipar o lock_time esbarra no CHECKLOCKTIMEVERIFY
+    let mut early = refund.clone();
+    early.lock_time = s.timeout - 1;
+    let signature = early.signature_for(0, &s.htlc_output, &s.funder.secret).unwrap();
+    early.inputs[0].script_sig.0[0] = Op::PushData(signature);
+    assert_eq!(
+        script_error(s.bc.submit_transaction(early)),
+        ScriptError::LockTimeNotReached { required: s.timeout, lock_time: s.timeout - 1 },
+    );
+
+    mine(&mut s.bc, &s.funder);
+    let refund_hash = refund.calculate_hash();
+    s.bc.submit_transaction(refund).unwrap();
+    mine(&mut s.bc, &s.funder);
+    assert!(s.bc.chain[s.timeout as usize + 1].transactions.iter().any(|tx| tx.calculate_hash() == refund_hash));
+}
+
+#[test]
+fn payee_cannot_take_refund_branch() {
+    let mut s = setup();
+    while next_height(&s.bc) <= s.timeout {
+        mine(&mut s.bc, &s.funder);
+    }
+    assert!(s.payee.refund_htlc(s.htlc_hash, 0, &s.htlc_output, &s.to, FEE).is_none());
+
+    let mut refund = s.funder.refund_htlc(s.htlc_hash, 0, &s.htlc_output, &s.to, FEE).unwrap();
+    let signature = refund.signature_for(0, &s.htlc_output, &s.payee.secret).unwrap();
+    refund.inputs[0].script_sig.0[0] = Op::PushData(signature);
+    assert_eq!(script_error(s.bc.submit_transaction(refund)), ScriptError::EvalFalse);
+}
+
+#[test]
+fn htlc_output_without_timeout_is_rejected() {
+    let mut bc = regtest_chain();
+    let funder = Wallet::from_seed("funder");
+    let coinbase = mine(&mut bc, &funder);
+
+    let terms = funder.htlc_terms(&Wallet::from_seed("payee").public, Hash::sha256(PREIMAGE), 0);
+    let spent = &coinbase.outputs[0];
+    let fund = funder.fund_htlc(coinbase.calculate_hash(), 0, spent, &terms, spent.value / 2, FEE).unwrap();
+    assert_eq!(bc.submit_transaction(fund).unwrap_err(), TxValidationError::InvalidHtlc { output_index: 0 });
+}
+
+#[test]
+fn htlc_output_with_invalid_key_is_rejected() {
+    let mut bc = regtest_chain();
+    let funder = Wallet::from_seed("funder");
+    let coinbase = mine(&mut bc, &funder);
+
+    // 32 bytes que não são um ponto da curva
+    let mut terms = funder.htlc_terms(&Wallet::from_seed("payee").public, Hash::sha256(PREIMAGE), 10);
+    terms.payee = [2; 32];
+    assert!(ed25519_dalek::VerifyingKey::from_bytes(&terms.payee).is_err());
+    let spent = &coinbase.outputs[0];
+    let fund = funder.fund_htlc(coinbase.calculate_hash(), 0, spent, &terms, spent.value / 2, FEE).unwrap();
+    assert_eq!(bc.submit_transaction(fund).unwrap_err(), TxValidationError::InvalidHtlc { output_index: 0 });
+}
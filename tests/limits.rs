@@ -0,0 +1,72 @@
+mod common;
+
+use common::{mine, next_block, pay, regtest_params, remine};
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::block::Block;
+use utxo_blockchain::models::blockchain::Blockchain;
+use utxo_blockchain::models::chain_params::ChainParams;
+use utxo_blockchain::models::errors::{BlockValidationError, TxValidationError};
+use utxo_blockchain::models::transaction::Transaction;
+
+/// Corrente com uma coinbase madura de alice e um bloco com um pagamento dela
+fn block_with_payment(params: ChainParams) -> (Blockchain, Block, Transaction) {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = Blockchain::new(params);
+    let coinbase = mine(&mut bc, &alice);
+    let tx = pay(&alice, &coinbase, 0, &Wallet::from_seed("bob"), 49);
+
+    let mut block = next_block(&mut bc, &Wallet::from_seed("miner"));
+    block.transactions.push(tx.clone());
+    remine(&mut block);
+    (bc, block, tx)
+}
+
+#[test]
+fn oversized_block_is_dropped_before_indexing() {
+    let (mut bc, block, _) = block_with_payment(regtest_params());
+    let size = block.serialized_size();
+    bc.params.max_block_size = size - 1;
+
+    let expected = BlockValidationError::BlockTooLarge { size, max: size - 1 };
+    assert_eq!(bc.validate_block(&block).unwrap_err(), expected);
+    assert_eq!(bc.add_block(block.clone()).unwrap_err(), expected);
+    assert!(!bc.block_index.contains_key(&block.header.calculate_hash()));
+
+    // No limite exato ainda cabe
+    bc.params.max_block_size = size;
+    bc.add_block(block).unwrap();
+}
+
+#[test]
+fn block_with_too_many_transactions_is_dropped_before_indexing() {
+    let mut params = regtest_params();
+    params.max_block_transactions = 1;
+    let (mut bc, block, _) = block_with_payment(params);
+
+    let expected = BlockValidationError::TooManyTransactions { count: 2, max: 1 };
+    assert_eq!(bc.validate_block(&block).unwrap_err(), expected);
+    assert_eq!(bc.add_block(block.clone()).unwrap_err(), expected);
+    assert!(!bc.block_index.contains_key(&block.header.calculate_hash()));
+
+    bc.params.max_block_transactions = 2;
+    bc.add_block(block).unwrap();
+}
+
+#[test]
+fn oversized_transaction_is_rejected() {
+    let (mut bc, block, tx) = block_with_payment(regtest_params());
+    let size = tx.serialized_size();
+    bc.params.max_tx_size = size - 1;
+
+    let expected = TxValidationError::TransactionTooLarge { size, max: size - 1 };
+    assert_eq!(bc.validate_transaction(&tx).unwrap_err(), expected);
+    assert_eq!(bc.submit_transaction(tx.clone()).unwrap_err(), expected);
+    assert!(!bc.mempool.contains(&tx.calculate_hash()));
+    assert_eq!(
+        bc.add_block(block).unwrap_err(),
+        BlockValidationError::InvalidTransaction { tx_hash: tx.calculate_hash(), error: expected },
+    );
+
+    bc.params.max_tx_size = size;
+    bc.submit_transaction(tx).unwrap();
+}
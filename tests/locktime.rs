@@ -0,0 +1,165 @@
+mod common;
+
+use common::{address, funded_chain, mine, next_block, pay, remine, spend_input};
+use utxo_blockchain::crypto::hashing::Hash;
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::errors::{BlockValidationError, TxValidationError};
+use utxo_blockchain::models::transaction::{Input, Output, Transaction, LOCKTIME_THRESHOLD, SEQUENCE_FINAL};
+
+/// Transação com `lock_time` e uma sequence que não desliga a trava
+fn with_lock_time(lock_time: u64) -> Transaction {
+    let owner = address(&Wallet::from_seed("dummy"));
+    let input = Input { sequence: SEQUENCE_FINAL - 1, ..Input::new(Hash::sha256(b"anterior"), 0) };
+    let mut tx = Transaction::new(vec![input], vec![Output::to_address(1, &owner)]);
+    tx.lock_time = lock_time;
+    tx
+}
+
+#[test]
+fn height_lock_releases_in_the_next_block() {
+    assert!(with_lock_time(0).is_final(0, 0));
+    let tx = with_lock_time(10);
+    assert!(!tx.is_final(10, i64::MAX));
+    assert!(tx.is_final(11, 0));
+
+    // Logo abaixo do limiar ainda é altura
+    let tx = with_lock_time(LOCKTIME_THRESHOLD - 1);
+    assert!(!tx.is_final(LOCKTIME_THRESHOLD - 1, i64::MAX));
+    assert!(tx.is_final(LOCKTIME_THRESHOLD, 0));
+}
+
+#[test]
+fn time_lock_compares_with_median_time_past() {
+    let time = LOCKTIME_THRESHOLD + 1_000;
+    let tx = with_lock_time(time);
+    assert!(!tx.is_final(u64::MAX, time as i64));
+    assert!(tx.is_final(0, time as i64 + 1));
+
+    let tx = with_lock_time(LOCKTIME_THRESHOLD);
+    assert!(!tx.is_final(u64::MAX, LOCKTIME_THRESHOLD as i64));
+    assert!(tx.is_final(0, LOCKTIME_THRESHOLD as i64 + 1));
+}
+
+#[test]
+fn final_sequences_disable_the_lock() {
+    let mut tx = with_lock_time(10);
+    tx.inputs[0].sequence = SEQUENCE_FINAL;
+    assert!(tx.is_final(0, 0));
+
+    // Basta um input não final para a trava valer
+    tx.inputs.push(Input { sequence: SEQUENCE_FINAL - 1, ..Input::new(Hash::sha256(b"outro"), 0) });
+    assert!(!tx.is_final(10, i64::MAX));
+}
+
+#[test]
+fn lock_time_beyond_i64_is_never_final() {
+    let tx = with_lock_time(u64::MAX);
+    assert!(!tx.is_final(u64::MAX, i64::MAX));
+    assert!(!with_lock_time(i64::MAX as u64 + 1).is_final(0, i64::MAX));
+    assert!(!with_lock_time(LOCKTIME_THRESHOLD).is_final(0, -1));
+}
+
+/// Gasta a coinbase com `lock_time` e uma sequence que não desliga a trava
+fn locked_spend(coinbase: &Transaction, lock_time: u64) -> Transaction {
+    let alice = Wallet::from_seed("alice");
+    let input = Input { sequence: SEQUENCE_FINAL - 1, ..Input::new(coinbase.calculate_hash(), 0) };
+    let mut tx = Transaction::new(vec![input], vec![Output::to_address(49, &address(&alice))]);
+    tx.lock_time = lock_time;
+    assert!(tx.sign_input(0, &coinbase.outputs[0], &alice.secret));
+    tx
+}
+
+/// Gasta a coinbase exigindo `blocks` confirmações dela
+fn relative_spend(coinbase: &Transaction, blocks: u16) -> Transaction {
+    let alice = Wallet::from_seed("alice");
+    let input = Input::with_relative_lock(coinbase.calculate_hash(), 0, blocks);
+    spend_input(&alice, coinbase, input, vec![Output::to_address(49, &address(&alice))])
+}
+
+#[test]
+fn mempool_waits_for_absolute_lock() {
+    let (mut bc, coinbase) = funded_chain();
+    let next_height = bc.chain.len() as u64;
+
+    let tx = locked_spend(&coinbase, next_height);
+    assert_eq!(
+        bc.submit_transaction(tx.clone()).unwrap_err(),
+        TxValidationError::LockTimeNotReached { lock_time: next_height },
+    );
+    mine(&mut bc, &Wallet::from_seed("miner"));
+    bc.submit_transaction(tx).unwrap();
+}
+
+#[test]
+fn block_with_unreached_absolute_lock_is_rejected() {
+    let (mut bc, coinbase) = funded_chain();
+    let next_height = bc.chain.len() as u64;
+    let tx = locked_spend(&coinbase, next_height);
+
+    let mut block = next_block(&mut bc, &Wallet::from_seed("miner"));
+    block.transactions.push(tx.clone());
+    remine(&mut block);
+    assert_eq!(
+        bc.add_block(block).unwrap_err(),
+        BlockValidationError::InvalidTransaction {
+            tx_hash: tx.calculate_hash(),
+            error: TxValidationError::LockTimeNotReached { lock_time: next_height },
+        },
+    );
+
+    let mut block = next_block(&mut bc, &Wallet::from_seed("miner"));
+    block.transactions.push(locked_spend(&coinbase, next_height - 1));
+    remine(&mut block);
+    bc.add_block(block).unwrap();
+}
+
+#[test]
+fn mempool_waits_for_relative_lock() {
+    let (mut bc, coinbase) = funded_chain();
+    let tx = relative_spend(&coinbase, 3);
+
+    // Criada na altura 1; o próximo bloco (altura 2) daria 1 confirmação
+    assert_eq!(
+        bc.submit_transaction(tx.clone()).unwrap_err(),
+        TxValidationError::RelativeLockNotReached { input_index: 0, required: 3, confirmations: 1 },
+    );
+    mine(&mut bc, &Wallet::from_seed("miner"));
+    assert_eq!(
+        bc.submit_transaction(tx.clone()).unwrap_err(),
+        TxValidationError::RelativeLockNotReached { input_index: 0, required: 3, confirmations: 2 },
+    );
+    mine(&mut bc, &Wallet::from_seed("miner"));
+    bc.submit_transaction(tx).unwrap();
+}
+
+#[test]
+fn block_with_unreached_relative_lock_is_rejected() {
+    let (mut bc, coinbase) = funded_chain();
+    mine(&mut bc, &Wallet::from_seed("miner"));
+    let tx = relative_spend(&coinbase, 3);
+
+    let mut block = next_block(&mut bc, &Wallet::from_seed("miner"));
+    block.transactions.push(tx.clone());
+    remine(&mut block);
+    assert_eq!(
+        bc.add_block(block).unwrap_err(),
+        BlockValidationError::InvalidTransaction {
+            tx_hash: tx.calculate_hash(),
+            error: TxValidationError::RelativeLockNotReached { input_index: 0, required: 3, confirmations: 2 },
+        },
+    );
+
+    mine(&mut bc, &Wallet::from_seed("miner"));
+    let mut block = next_block(&mut bc, &Wallet::from_seed("miner"));
+    block.transactions.push(tx);
+    remine(&mut block);
+    bc.add_block(block).unwrap();
+}
+
+#[test]
+fn final_sequence_has_no_relative_lock() {
+    let (mut bc, coinbase) = funded_chain();
+    let alice = Wallet::from_seed("alice");
+    assert_eq!(Input::new(coinbase.calculate_hash(), 0).relative_lock(), None);
+    bc.submit_transaction(pay(&alice, &coinbase, 0, &alice, 49)).unwrap();
+}
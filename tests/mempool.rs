@@ -0,0 +1,203 @@
+mod common;
+
+use common::{address, dummy_tx, mine, next_block, pay, regtest_chain, spend, TestClock};
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::blockchain::Blockchain;
+use utxo_blockchain::models::errors::TxValidationError;
+use utxo_blockchain::models::mempool::{Mempool, MempoolPolicy};
+use utxo_blockchain::models::transaction::{Output, Transaction};
+
+const DAY: i64 = 24 * 60 * 60;
+
+/// Mempool que comporta exatamente `count` transações dummy
+fn pool_for(count: usize) -> Mempool {
+    let size = dummy_tx(b"medida", 1).serialized_size();
+    Mempool::new(MempoolPolicy { max_size: size * count, ..MempoolPolicy::default() })
+}
+
+/// Corrente com relógio manual e uma transação de alice (taxa 1) na mempool
+fn chain_with_pending_tx() -> (Blockchain, TestClock, Transaction) {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    let genesis_time = bc.chain[0].header.timestamp;
+    let clock = TestClock::install(&mut bc, genesis_time);
+    clock.advance(600);
+    let coinbase = mine(&mut bc, &alice);
+    let tx = pay(&alice, &coinbase, 0, &alice, 49);
+    bc.submit_transaction(tx.clone()).unwrap();
+    (bc, clock, tx)
+}
+
+#[test]
+fn old_transactions_expire_with_descendants() {
+    let (mut bc, clock, parent) = chain_with_pending_tx();
+    let child = pay(&Wallet::from_seed("alice"), &parent, 0, &Wallet::from_seed("bob"), 48);
+    clock.advance(DAY);
+    bc.submit_transaction(child.clone()).unwrap();
+
+    // O filho é mais novo, mas sem o pai não tem como ficar
+    clock.advance(bc.mempool.policy.expiry - DAY - 1);
+    assert!(bc.expire_mempool().is_empty());
+    clock.advance(1);
+    let expired = bc.expire_mempool();
+    assert_eq!(expired.len(), 2);
+    assert!(expired.contains(&child.calculate_hash()));
+    assert!(bc.mempool.is_empty());
+}
+
+#[test]
+fn idle_miner_still_expires() {
+    let (mut bc, clock, tx) = chain_with_pending_tx();
+    clock.advance(bc.mempool.policy.expiry);
+
+    // Ninguém mandou transação nova: montar o bloco já descarta a velha
+    let block = bc.create_next_block(&address(&Wallet::from_seed("miner")));
+    assert_eq!(block.transactions.len(), 1);
+    assert!(!bc.mempool.contains(&tx.calculate_hash()));
+}
+
+#[test]
+fn connecting_a_block_expires() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    let genesis_time = bc.chain[0].header.timestamp;
+    let clock = TestClock::install(&mut bc, genesis_time);
+    clock.advance(600);
+    let coinbase = mine(&mut bc, &alice);
+
+    // Bloco de outro minerador, montado antes de a transação chegar
+    let block = next_block(&mut bc, &Wallet::from_seed("bob"));
+    let tx = pay(&alice, &coinbase, 0, &alice, 49);
+    bc.submit_transaction(tx.clone()).unwrap();
+
+    clock.advance(bc.mempool.policy.expiry);
+    bc.add_block(block).unwrap();
+    assert!(!bc.mempool.contains(&tx.calculate_hash()));
+}
+
+/// Salva uma corrente cuja transação pendente entrou `age` segundos atrás
+/// pelo relógio real, e recarrega com o relógio do sistema
+fn reload_with_tx_aged(age: i64) -> (Blockchain, Transaction, i64) {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    let entry_time = chrono::Utc::now().timestamp() - age;
+    let clock = TestClock::install(&mut bc, entry_time - 600);
+    let coinbase = mine(&mut bc, &alice);
+    clock.set(entry_time);
+    let tx = pay(&alice, &coinbase, 0, &alice, 49);
+    bc.submit_transaction(tx.clone()).unwrap();
+
+    let path = std::env::temp_dir().join(format!("mempool_times_{}_{}.json", std::process::id(), age));
+    let path = path.to_str().unwrap();
+    bc.save_to_file(path).unwrap();
+    let loaded = Blockchain::load_from_file(path, bc.params.clone()).unwrap();
+    std::fs::remove_file(path).unwrap();
+    (loaded, tx, entry_time)
+}
+
+#[test]
+fn reload_keeps_entry_times() {
+    let (loaded, tx, entry_time) = reload_with_tx_aged(3600);
+    assert_eq!(loaded.mempool.get(&tx.calculate_hash()).unwrap().time, entry_time);
+}
+
+#[test]
+fn reload_drops_what_expired_while_offline() {
+    let expiry = MempoolPolicy::default().expiry;
+    let (loaded, _, _) = reload_with_tx_aged(expiry + 60);
+    assert!(loaded.mempool.is_empty());
+}
+
+#[test]
+fn lowest_fee_rate_is_trimmed_first() {
+    let mut pool = pool_for(2);
+    let (a, b, c) = (dummy_tx(b"a", 1), dummy_tx(b"b", 1), dummy_tx(b"c", 1));
+    pool.insert(a.clone(), 5, 0).unwrap();
+    pool.insert(b.clone(), 3, 0).unwrap();
+
+    let evicted = pool.insert(c.clone(), 4, 0).unwrap();
+    assert_eq!(evicted, vec![b.calculate_hash()]);
+    assert!(pool.contains(&a.calculate_hash()) && pool.contains(&c.calculate_hash()));
+    assert!(pool.total_size() <= pool.policy.max_size);
+}
+
+#[test]
+fn paying_least_when_full_is_refused() {
+    let mut pool = pool_for(1);
+    let (a, b) = (dummy_tx(b"a", 1), dummy_tx(b"b", 1));
+    pool.insert(a.clone(), 5, 0).unwrap();
+
+    let rate = (5 * 1000u64).div_ceil(a.serialized_size() as u64);
+    assert_eq!(pool.insert(b, 2, 0).unwrap_err(), TxValidationError::MempoolFull { min_fee_rate_per_kb: rate });
+    assert!(pool.contains(&a.calculate_hash()));
+}
+
+#[test]
+fn min_fee_rate_does_not_overflow() {
+    let mut pool = pool_for(1);
+    pool.insert(dummy_tx(b"rica", 1), u64::MAX, 0).unwrap();
+    assert_eq!(
+        pool.insert(dummy_tx(b"pobre", 1), 1, 0).unwrap_err(),
+        TxValidationError::MempoolFull { min_fee_rate_per_kb: u64::MAX },
+    );
+}
+
+#[test]
+fn fee_below_min_relay_is_rejected() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    bc.mempool.policy.min_relay_fee_per_kb = 100;
+    let coinbase = mine(&mut bc, &alice);
+
+    let size = pay(&alice, &coinbase, 0, &alice, 1).serialized_size();
+    let required = (size as u64 * 100).div_ceil(1000);
+    assert_eq!(
+        bc.submit_transaction(pay(&alice, &coinbase, 0, &alice, 50 - required + 1)).unwrap_err(),
+        TxValidationError::FeeTooLow { fee: required - 1, required },
+    );
+    bc.submit_transaction(pay(&alice, &coinbase, 0, &alice, 50 - required)).unwrap();
+}
+
+#[test]
+fn unconfirmed_chain_respects_ancestor_limit() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    bc.mempool.policy = MempoolPolicy { max_ancestors: 3, ..MempoolPolicy::default() };
+    let coinbase = mine(&mut bc, &alice);
+
+    // A própria transação conta: três em cadeia cabem, a quarta não
+    let mut parent = coinbase;
+    for value in [49, 48, 47] {
+        let tx = pay(&alice, &parent, 0, &alice, value);
+        bc.submit_transaction(tx.clone()).unwrap();
+        parent = tx;
+    }
+    let tx = pay(&alice, &parent, 0, &alice, 46);
+    assert_eq!(bc.submit_transaction(tx.clone()).unwrap_err(), TxValidationError::TooManyAncestors { count: 4, max: 3 });
+    assert!(!bc.mempool.contains(&tx.calculate_hash()));
+
+    // Com um bloco a cadeia confirma e o limite volta a ter folga
+    mine(&mut bc, &alice);
+    bc.submit_transaction(tx).unwrap();
+}
+
+#[test]
+fn unconfirmed_parent_respects_descendant_limit() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    bc.mempool.policy = MempoolPolicy { max_descendants: 3, ..MempoolPolicy::default() };
+    let coinbase = mine(&mut bc, &alice);
+    let parent = spend(&alice, &coinbase, 0, vec![Output::to_address(16, &address(&alice)); 3]);
+    bc.submit_transaction(parent.clone()).unwrap();
+
+    // O pai e dois filhos somam três; o terceiro filho passaria do limite
+    for output_index in 0..2 {
+        bc.submit_transaction(pay(&alice, &parent, output_index, &alice, 15)).unwrap();
+    }
+    let tx = pay(&alice, &parent, 2, &alice, 15);
+    assert_eq!(
+        bc.submit_transaction(tx.clone()).unwrap_err(),
+        TxValidationError::TooManyDescendants { tx_hash: parent.calculate_hash(), count: 4, max: 3 },
+    );
+    assert!(!bc.mempool.contains(&tx.calculate_hash()));
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{address, mine, regtest_chain, script_error, spend};
+use utxo_blockchain::crypto::hashing::Hash;
+use utxo_blockchain::crypto::wallet::{build_multisig_spend, Wallet};
+use utxo_blockchain::models::blockchain::Blockchain;
+use utxo_blockchain::models::errors::{ScriptError, TxValidationError};
+use utxo_blockchain::models::script::{Op, Script};
+use utxo_blockchain::models::transaction::{Output, Transaction};
+
+/// Corrente com um output 2-de-3 (alice, bob, carol) confirmado
+struct Setup {
+    bc: Blockchain,
+    owners: [Wallet; 3],
+    multisig_hash: Hash,
+    multisig_output: Output,
+}
+
+fn setup() -> Setup {
+    let owners = ["alice", "bob", "carol"].map(Wallet::from_seed);
+    let mut bc = regtest_chain();
+    let coinbase = mine(&mut bc, &owners[0]);
+
+    let keys = owners.each_ref().map(|owner| owner.public);
+    let fund = spend(&owners[0], &coinbase, 0, vec![Output::multisig(49, 2, &keys)]);
+    let multisig_hash = fund.calculate_hash();
+    let multisig_output = fund.outputs[0].clone();
+    bc.submit_transaction(fund).unwrap();
+    mine(&mut bc, &owners[0]);
+    Setup { bc, owners, multisig_hash, multisig_output }
+}
+
+/// Gasto do multisig ainda sem assinaturas
+fn unsigned(s: &Setup) -> Transaction {
+    let to = address(&Wallet::from_seed("destino"));
+    build_multisig_spend(s.multisig_hash, 0, vec![Output::to_address(48, &to)])
+}
+
+#[test]
+fn threshold_signatures_spend() {
+    let mut s = setup();
+    let mut tx = unsigned(&s);
+    // Carol assina primeiro; a assinatura de alice entra antes dela
+    assert_eq!(s.owners[2].cosign_multisig(&mut tx, 0, &s.multisig_output), Some(1));
+    assert_eq!(s.owners[0].cosign_multisig(&mut tx, 0, &s.multisig_output), Some(2));
+    assert!(s.bc.submit_transaction(tx).is_ok());
+}
+
+#[test]
+fn one_signature_short_fails() {
+    let mut s = setup();
+    let mut tx = unsigned(&s);
+    assert_eq!(s.owners[1].cosign_multisig(&mut tx, 0, &s.multisig_output), Some(1));
+    assert_eq!(script_error(s.bc.submit_transaction(tx)), ScriptError::StackUnderflow);
+}
+
+#[test]
+fn signatures_out_of_key_order_fail() {
+    let mut s = setup();
+    let mut tx = unsigned(&s);
+    let signature = |wallet: &Wallet| Op::PushData(tx.signature_for(0, &s.multisig_output, &wallet.secret).unwrap());
+    let (alice, carol) = (signature(&s.owners[0]), signature(&s.owners[2]));
+
+    tx.inputs[0].script_sig = Script::new(vec![carol.clone(), alice.clone()]);
+    assert_eq!(script_error(s.bc.submit_transaction(tx.clone())), ScriptError::EvalFalse);
+
+    tx.inputs[0].script_sig = Script::new(vec![alice, carol]);
+    assert!(s.bc.submit_transaction(tx).is_ok());
+}
+
+#[test]
+fn outsider_cannot_cosign() {
+    let s = setup();
+    let mut tx = unsigned(&s);
+    assert_eq!(Wallet::from_seed("mallory").cosign_multisig(&mut tx, 0, &s.multisig_output), None);
+    assert!(tx.inputs[0].script_sig.0.is_empty());
+}
+
+#[test]
+fn unspendable_multisig_outputs_are_rejected() {
+    let alice = Wallet::from_seed("alice");
+    let [a, bob, carol] = ["alice", "bob", "carol"].map(|seed| Wallet::from_seed(seed).public);
+    // Uma chave que não é ed25519 só entra montando o script à mão
+    let bad_key = Script::new(vec![
+        Op::PushInt(1),
+        Op::PushData(a.as_bytes().to_vec()),
+        Op::PushData(vec![0xff; 20]),
+        Op::PushInt(2),
+        Op::CheckMultiSig,
+    ]);
+
+    let cases = [
+        Script::multisig(0, &[a, bob]),             // Limiar zero
+        Script::multisig(3, &[a, bob]),             // Limiar acima do número de chaves
+        Script::multisig(2, &[a, a, carol]),        // Chave repetida
+        bad_key,
+    ];
+    for script_pubkey in cases {
+        let mut bc = regtest_chain();
+        let coinbase = mine(&mut bc, &alice);
+        let fund = spend(&alice, &coinbase, 0, vec![Output { value: 49, script_pubkey }]);
+        assert_eq!(bc.submit_transaction(fund).unwrap_err(), TxValidationError::InvalidMultisig { output_index: 0 });
+    }
+}
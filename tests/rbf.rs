@@ -0,0 +1,118 @@
+mod common;
+
+use common::{address, dummy_tx, funded_chain, pay, spend_input};
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::errors::TxValidationError;
+use utxo_blockchain::models::mempool::{Mempool, MempoolPolicy};
+use utxo_blockchain::models::transaction::{Input, Output, Transaction, SEQUENCE_FINAL, SEQUENCE_REPLACEABLE};
+
+/// Gasta a coinbase pagando `value` a `to`, com a sequence dada
+fn spend_coinbase(coinbase: &Transaction, sequence: u32, to: &str, value: u64) -> Transaction {
+    let input = Input { sequence, ..Input::new(coinbase.calculate_hash(), 0) };
+    let outputs = vec![Output::to_address(value, &address(&Wallet::from_seed(to)))];
+    spend_input(&Wallet::from_seed("alice"), coinbase, input, outputs)
+}
+
+#[test]
+fn only_opt_in_transactions_are_replaced() {
+    let (mut bc, coinbase) = funded_chain();
+    let original = spend_coinbase(&coinbase, SEQUENCE_FINAL, "bob", 49);
+    bc.submit_transaction(original.clone()).unwrap();
+    assert!(!original.is_replaceable());
+
+    assert_eq!(
+        bc.submit_transaction(spend_coinbase(&coinbase, SEQUENCE_FINAL, "carol", 40)).unwrap_err(),
+        TxValidationError::MempoolConflict { input_index: 0, conflicting_tx: original.calculate_hash() },
+    );
+
+    let (mut bc, coinbase) = funded_chain();
+    let original = spend_coinbase(&coinbase, SEQUENCE_REPLACEABLE, "bob", 49);
+    bc.submit_transaction(original.clone()).unwrap();
+    assert!(original.is_replaceable());
+
+    let replacement = spend_coinbase(&coinbase, SEQUENCE_FINAL, "carol", 40);
+    let accepted = bc.submit_transaction(replacement.clone()).unwrap();
+    assert_eq!(accepted.replaced, vec![original.calculate_hash()]);
+    assert!(bc.mempool.contains(&replacement.calculate_hash()));
+    assert!(!bc.mempool.contains(&original.calculate_hash()));
+}
+
+#[test]
+fn replacement_must_pay_a_higher_fee_rate() {
+    let (mut bc, coinbase) = funded_chain();
+    let original = spend_coinbase(&coinbase, SEQUENCE_REPLACEABLE, "bob", 45);
+    bc.submit_transaction(original.clone()).unwrap();
+
+    // Mesmo tamanho e mesma taxa
+    assert_eq!(
+        bc.submit_transaction(spend_coinbase(&coinbase, SEQUENCE_REPLACEABLE, "carol", 45)).unwrap_err(),
+        TxValidationError::ReplacementFeeRateTooLow { conflicting_tx: original.calculate_hash() },
+    );
+    assert!(bc.mempool.contains(&original.calculate_hash()));
+
+    bc.submit_transaction(spend_coinbase(&coinbase, SEQUENCE_REPLACEABLE, "carol", 44)).unwrap();
+}
+
+#[test]
+fn replacement_must_cover_every_evicted_fee() {
+    let alice = Wallet::from_seed("alice");
+    let (mut bc, coinbase) = funded_chain();
+
+    // Pai com taxa 1 e um filho generoso de taxa 10
+    let parent = spend_coinbase(&coinbase, SEQUENCE_REPLACEABLE, "alice", 49);
+    let child = pay(&alice, &parent, 0, &alice, 39);
+    bc.submit_transaction(parent.clone()).unwrap();
+    bc.submit_transaction(child.clone()).unwrap();
+
+    // Taxa por byte bem maior que a do pai, mas não paga o que sai (11) mais o relay
+    let replacement = spend_coinbase(&coinbase, SEQUENCE_FINAL, "carol", 45);
+    let required = 11 + bc.mempool.policy.min_relay_fee(replacement.serialized_size()).max(1);
+    assert_eq!(
+        bc.submit_transaction(replacement).unwrap_err(),
+        TxValidationError::InsufficientReplacementFee { fee: 5, required },
+    );
+    assert_eq!(bc.mempool.len(), 2);
+
+    let replacement = spend_coinbase(&coinbase, SEQUENCE_FINAL, "carol", 50 - required);
+    let accepted = bc.submit_transaction(replacement).unwrap();
+    assert_eq!(accepted.replaced, vec![parent.calculate_hash(), child.calculate_hash()]);
+    assert_eq!(bc.mempool.len(), 1);
+}
+
+#[test]
+fn replacement_cannot_spend_what_it_replaces() {
+    let alice = Wallet::from_seed("alice");
+    let (mut bc, coinbase) = funded_chain();
+    let original = spend_coinbase(&coinbase, SEQUENCE_REPLACEABLE, "alice", 49);
+    bc.submit_transaction(original.clone()).unwrap();
+
+    let inputs = vec![Input::new(coinbase.calculate_hash(), 0), Input::new(original.calculate_hash(), 0)];
+    let mut replacement = Transaction::new(inputs, vec![Output::to_address(60, &address(&alice))]);
+    assert!(replacement.sign_input(0, &coinbase.outputs[0], &alice.secret));
+    assert!(replacement.sign_input(1, &original.outputs[0], &alice.secret));
+
+    assert_eq!(
+        bc.submit_transaction(replacement).unwrap_err(),
+        TxValidationError::ReplacementSpendsConflict { input_index: 1 },
+    );
+    assert!(bc.mempool.contains(&original.calculate_hash()));
+}
+
+#[test]
+fn refused_insert_keeps_what_it_would_have_evicted() {
+    let size = dummy_tx(b"medida", 1).serialized_size();
+    let mut pool = Mempool::new(MempoolPolicy { max_size: size * 2, ..MempoolPolicy::default() });
+    let (rich, poor) = (dummy_tx(b"rica", 1), dummy_tx(b"pobre", 1));
+    pool.insert(rich.clone(), 10, 0).unwrap();
+    pool.insert(poor.clone(), 1, 0).unwrap();
+
+    // Maior que as outras: despejar a pobre não basta, e ela mesma paga menos que a rica
+    let big = dummy_tx(b"grande", 2);
+    assert!(big.serialized_size() < size * 3);
+    assert!(matches!(pool.insert(big.clone(), 3, 0), Err(TxValidationError::MempoolFull { .. })));
+
+    assert!(!pool.contains(&big.calculate_hash()));
+    assert!(pool.contains(&rich.calculate_hash()));
+    assert!(pool.contains(&poor.calculate_hash()));
+    assert_eq!(pool.total_size(), size * 2);
+}
@@ -0,0 +1,156 @@
+use utxo_blockchain::crypto::hashing::Hash;
+use utxo_blockchain::models::blockchain::UtxoEntry;
+use utxo_blockchain::models::chain_params::ChainParams;
+use utxo_blockchain::models::errors::ScriptError;
+use utxo_blockchain::models::script::{Op, Script, ScriptLimits};
+use utxo_blockchain::models::transaction::{Input, Output, Transaction, LOCKTIME_THRESHOLD, SEQUENCE_FINAL};
+
+const SPENT_HEIGHT: u64 = 5;
+
+fn limits() -> ScriptLimits {
+    ChainParams::regtest().script_limits
+}
+
+/// Transação de um input com o desbloqueio dado
+fn spending(unlocking: Vec<Op>) -> Transaction {
+    let mut tx = Transaction::new(vec![Input::new(Hash::sha256(b"anterior"), 0)], Vec::new());
+    tx.inputs[0].script_sig = Script::new(unlocking);
+    tx
+}
+
+/// Avalia o input 0 de `tx` contra `locking`, num bloco na altura `height`
+fn eval(tx: &Transaction, locking: Vec<Op>, height: u64, limits: &ScriptLimits) -> Result<(), ScriptError> {
+    let output = Output { value: 1, script_pubkey: Script::new(locking) };
+    let entry = UtxoEntry { output, height: SPENT_HEIGHT, is_coinbase: false };
+    tx.verify_input(0, &entry, height, limits)
+}
+
+fn run(unlocking: Vec<Op>, locking: Vec<Op>) -> Result<(), ScriptError> {
+    eval(&spending(unlocking), locking, SPENT_HEIGHT + 1, &limits())
+}
+
+#[test]
+fn stack_and_hash_opcodes() {
+    let data = b"abc".to_vec();
+    let sha = Hash::sha256(&data).as_bytes().to_vec();
+    assert_eq!(run(vec![Op::PushData(data.clone())], vec![Op::Sha256, Op::PushData(sha.clone()), Op::Equal]), Ok(()));
+    assert_eq!(run(vec![Op::PushData(data.clone())], vec![Op::Hash256, Op::PushData(sha), Op::Equal]), Err(ScriptError::EvalFalse));
+    assert_eq!(run(vec![Op::PushInt(7)], vec![Op::Dup, Op::EqualVerify, Op::PushInt(1)]), Ok(()));
+    assert_eq!(run(vec![Op::PushInt(1), Op::PushInt(0)], vec![Op::Drop]), Ok(()));
+    assert_eq!(
+        run(vec![Op::PushInt(1), Op::PushInt(2)], vec![Op::EqualVerify, Op::PushInt(1)]),
+        Err(ScriptError::EqualVerifyFailed),
+    );
+    assert_eq!(run(vec![Op::PushInt(0)], vec![Op::Verify, Op::PushInt(1)]), Err(ScriptError::VerifyFailed));
+    assert_eq!(run(vec![], vec![Op::Dup]), Err(ScriptError::StackUnderflow));
+}
+
+#[test]
+fn result_must_be_true() {
+    assert_eq!(run(vec![], vec![]), Err(ScriptError::EvalFalse));
+    assert_eq!(run(vec![], vec![Op::PushInt(0)]), Err(ScriptError::EvalFalse));
+    // Zero com mais bytes continua falso
+    assert_eq!(run(vec![Op::PushData(vec![0, 0, 0])], vec![]), Err(ScriptError::EvalFalse));
+    assert_eq!(run(vec![Op::PushData(vec![0, 1])], vec![]), Ok(()));
+}
+
+#[test]
+fn result_must_be_alone_on_the_stack() {
+    assert_eq!(run(vec![Op::PushInt(1), Op::PushInt(1)], vec![]), Err(ScriptError::CleanStack { size: 2 }));
+    assert_eq!(run(vec![Op::PushInt(0)], vec![Op::PushInt(1)]), Err(ScriptError::CleanStack { size: 2 }));
+    // Falso no topo continua sendo EvalFalse, mesmo com sobras
+    assert_eq!(run(vec![Op::PushInt(1), Op::PushInt(0)], vec![]), Err(ScriptError::EvalFalse));
+}
+
+#[test]
+fn unlocking_script_must_be_push_only() {
+    assert_eq!(run(vec![Op::PushInt(1), Op::Dup], vec![Op::Equal]), Err(ScriptError::NotPushOnly));
+    assert_eq!(run(vec![Op::PushInt(1), Op::If, Op::EndIf], vec![Op::PushInt(1)]), Err(ScriptError::NotPushOnly));
+}
+
+#[test]
+fn conditionals_pick_a_branch() {
+    let locking = vec![Op::If, Op::PushInt(1), Op::Else, Op::PushInt(0), Op::EndIf];
+    assert_eq!(run(vec![Op::PushInt(1)], locking.clone()), Ok(()));
+    assert_eq!(run(vec![Op::PushInt(0)], locking), Err(ScriptError::EvalFalse));
+
+    // O ramo que não executa não mexe na pilha
+    assert_eq!(run(vec![Op::PushInt(0)], vec![Op::If, Op::Drop, Op::Drop, Op::EndIf, Op::PushInt(1)]), Ok(()));
+    assert_eq!(run(vec![Op::PushInt(1)], vec![Op::EndIf]), Err(ScriptError::UnbalancedConditional));
+    assert_eq!(run(vec![Op::PushInt(1)], vec![Op::If, Op::PushInt(1)]), Err(ScriptError::UnbalancedConditional));
+}
+
+#[test]
+fn resource_limits_are_enforced() {
+    let tight = ScriptLimits { max_ops: 2, max_stack_size: 2, max_element_size: 4, ..limits() };
+    // Números ocupam 8 bytes: aqui os elementos vão como dados curtos
+    let one = || Op::PushData(vec![1]);
+    let tx = spending(vec![one()]);
+
+    // Pushes não contam como operações
+    assert_eq!(eval(&tx, vec![one(), Op::Equal, Op::Verify, one()], 6, &tight), Ok(()));
+    assert_eq!(eval(&tx, vec![Op::Dup, Op::Drop, Op::Dup], 6, &tight), Err(ScriptError::TooManyOps { max: 2 }));
+
+    let overflow = spending(vec![one(), one(), one()]);
+    assert_eq!(eval(&overflow, vec![], 6, &tight), Err(ScriptError::StackOverflow { max: 2 }));
+
+    let large = spending(vec![Op::PushData(vec![1; 5])]);
+    assert_eq!(eval(&large, vec![], 6, &tight), Err(ScriptError::ElementTooLarge { size: 5, max: 4 }));
+
+    let size = Script::new(vec![Op::PushData(vec![1; 64])]).serialized_size();
+    let small = ScriptLimits { max_script_size: size - 1, ..limits() };
+    let big = spending(vec![Op::PushData(vec![1; 64])]);
+    assert_eq!(eval(&big, vec![], 6, &small), Err(ScriptError::ScriptTooLarge { size, max: size - 1 }));
+}
+
+#[test]
+fn numbers_are_at_most_eight_bytes() {
+    let locking = vec![Op::CheckSequenceVerify, Op::PushInt(1)];
+    assert_eq!(run(vec![Op::PushData(vec![1; 9])], locking.clone()), Err(ScriptError::InvalidNumber));
+    assert_eq!(run(vec![Op::PushData(vec![1])], locking), Ok(()));
+}
+
+/// Travado com `<required> CHECKLOCKTIMEVERIFY 1`, gasto com o lock_time e a sequence dados
+fn cltv(required: i64, lock_time: u64, sequence: u32) -> Result<(), ScriptError> {
+    let mut tx = spending(vec![]);
+    tx.lock_time = lock_time;
+    tx.inputs[0].sequence = sequence;
+    eval(&tx, vec![Op::PushInt(required), Op::CheckLockTimeVerify, Op::PushInt(1)], 1, &limits())
+}
+
+#[test]
+fn check_lock_time_compares_with_transaction_lock_time() {
+    let sequence = SEQUENCE_FINAL - 1;
+    // A altura do bloco não importa: só o lock_time da transação
+    assert_eq!(cltv(10, 10, sequence), Ok(()));
+    assert_eq!(cltv(10, 11, sequence), Ok(()));
+    assert_eq!(cltv(10, 9, sequence), Err(ScriptError::LockTimeNotReached { required: 10, lock_time: 9 }));
+    assert_eq!(cltv(-1, 10, sequence), Err(ScriptError::NegativeLockTime));
+
+    let time = LOCKTIME_THRESHOLD + 100;
+    assert_eq!(cltv(time as i64, time, sequence), Ok(()));
+    assert_eq!(
+        cltv(10, time, sequence),
+        Err(ScriptError::LockTimeTypeMismatch { required: 10, lock_time: time }),
+    );
+    assert_eq!(
+        cltv(time as i64, 10, sequence),
+        Err(ScriptError::LockTimeTypeMismatch { required: time, lock_time: 10 }),
+    );
+}
+
+#[test]
+fn check_lock_time_requires_non_final_sequence() {
+    assert_eq!(cltv(10, 10, SEQUENCE_FINAL), Err(ScriptError::FinalSequence));
+}
+
+#[test]
+fn check_sequence_counts_confirmations_of_spent_output() {
+    let tx = spending(vec![]);
+    let locking = vec![Op::PushInt(3), Op::CheckSequenceVerify, Op::PushInt(1)];
+    assert_eq!(eval(&tx, locking.clone(), SPENT_HEIGHT + 3, &limits()), Ok(()));
+    assert_eq!(
+        eval(&tx, locking, SPENT_HEIGHT + 2, &limits()),
+        Err(ScriptError::SequenceNotReached { required: 3, confirmations: 2 }),
+    );
+}
@@ -0,0 +1,172 @@
+mod common;
+
+use common::address;
+use utxo_blockchain::crypto::hashing::Hash;
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::blockchain::UtxoEntry;
+use utxo_blockchain::models::chain_params::ChainParams;
+use utxo_blockchain::models::sighash::SigHashType;
+use utxo_blockchain::models::transaction::{Input, Output, Transaction};
+
+/// Três inputs de 10 pagos a alice e três outputs para bob
+fn fixture() -> (Wallet, Transaction, Vec<Output>) {
+    let alice = Wallet::from_seed("alice");
+    let bob = Wallet::from_seed("bob");
+    let inputs = (0..3).map(|i| Input::new(Hash::sha256(&[i]), 0)).collect();
+    let outputs = (1..=3).map(|value| Output::to_address(value, &address(&bob))).collect();
+    let spent = vec![Output::to_address(10, &address(&alice)); 3];
+    (alice, Transaction::new(inputs, outputs), spent)
+}
+
+fn signed(sighash: SigHashType, input_index: usize) -> (Transaction, Vec<Output>) {
+    let (alice, mut tx, spent) = fixture();
+    assert!(tx.sign_input_with(input_index, &spent[input_index], &alice.secret, sighash));
+    (tx, spent)
+}
+
+fn is_valid(tx: &Transaction, input_index: usize, spent: &Output) -> bool {
+    let entry = UtxoEntry { output: spent.clone(), height: 0, is_coinbase: false };
+    tx.verify_input(input_index, &entry, 1, &ChainParams::regtest().script_limits).is_ok()
+}
+
+fn extra_output() -> Output {
+    Output::to_address(7, &address(&Wallet::from_seed("mallory")))
+}
+
+#[test]
+fn all_commits_every_input_and_output() {
+    let (tx, spent) = signed(SigHashType::ALL, 0);
+    assert!(is_valid(&tx, 0, &spent[0]));
+
+    let mut changed = tx.clone();
+    changed.outputs[2].value += 1;
+    assert!(!is_valid(&changed, 0, &spent[0]));
+
+    let mut changed = tx.clone();
+    changed.outputs.push(extra_output());
+    assert!(!is_valid(&changed, 0, &spent[0]));
+
+    let mut changed = tx.clone();
+    changed.inputs[2].sequence -= 1;
+    assert!(!is_valid(&changed, 0, &spent[0]));
+
+    let mut changed = tx.clone();
+    changed.inputs.push(Input::new(Hash::sha256(b"outro"), 0));
+    assert!(!is_valid(&changed, 0, &spent[0]));
+
+    // Assinaturas dos outros inputs não entram na mensagem
+    let mut changed = tx.clone();
+    changed.inputs[1].script_sig = tx.inputs[0].script_sig.clone();
+    assert!(is_valid(&changed, 0, &spent[0]));
+}
+
+#[test]
+fn signature_commits_the_spent_output() {
+    let (tx, spent) = signed(SigHashType::ALL, 0);
+    let mut other = spent[0].clone();
+    other.value += 1;
+    assert!(!is_valid(&tx, 0, &other));
+}
+
+#[test]
+fn none_leaves_outputs_open() {
+    let (tx, spent) = signed(SigHashType::NONE, 0);
+
+    let mut changed = tx.clone();
+    changed.outputs = vec![extra_output()];
+    assert!(is_valid(&changed, 0, &spent[0]));
+
+    let mut changed = tx.clone();
+    changed.inputs[1].prev_tx_hash = Hash::sha256(b"outro");
+    assert!(!is_valid(&changed, 0, &spent[0]));
+}
+
+#[test]
+fn single_commits_only_the_paired_output() {
+    let (tx, spent) = signed(SigHashType::SINGLE, 1);
+
+    let mut changed = tx.clone();
+    changed.outputs[0].value += 1;
+    changed.outputs[2].value += 1;
+    changed.outputs.push(extra_output());
+    assert!(is_valid(&changed, 1, &spent[1]));
+
+    let mut changed = tx.clone();
+    changed.outputs[1].value += 1;
+    assert!(!is_valid(&changed, 1, &spent[1]));
+
+    // Sem ANYONECANPAY os inputs continuam comprometidos
+    let mut changed = tx.clone();
+    changed.inputs.pop();
+    assert!(!is_valid(&changed, 1, &spent[1]));
+}
+
+#[test]
+fn single_without_paired_output_cannot_sign() {
+    let (alice, mut tx, spent) = fixture();
+    tx.outputs.truncate(1);
+
+    let mut single = tx.clone();
+    single.inputs[1].sighash = SigHashType::SINGLE;
+    assert_eq!(single.get_data_to_sign(1, &spent[1]), None);
+    assert!(!tx.sign_input_with(1, &spent[1], &alice.secret, SigHashType::SINGLE));
+
+    // Uma assinatura feita com o output presente não vale depois que ele some
+    let (mut tx, spent) = signed(SigHashType::SINGLE, 1);
+    tx.outputs.truncate(1);
+    assert!(!is_valid(&tx, 1, &spent[1]));
+}
+
+#[test]
+fn anyone_can_pay_commits_only_its_own_input() {
+    let (tx, spent) = signed(SigHashType::ALL.anyone_can_pay(), 1);
+
+    // Outros inputs podem entrar e sair, e o assinado pode mudar de posição
+    let mut changed = tx.clone();
+    changed.inputs.remove(0);
+    changed.inputs.push(Input::new(Hash::sha256(b"outro"), 0));
+    assert!(is_valid(&changed, 0, &spent[1]));
+
+    let mut changed = tx.clone();
+    changed.outputs[0].value += 1;
+    assert!(!is_valid(&changed, 1, &spent[1]));
+
+    let mut changed = tx.clone();
+    changed.inputs[1].sequence -= 1;
+    assert!(!is_valid(&changed, 1, &spent[1]));
+}
+
+#[test]
+fn anyone_can_pay_commits_the_signed_input_not_the_last() {
+    // `swap_remove` traz o último input para a posição 1: a mensagem precisa
+    // conter o input 1 original, e não o que foi movido
+    let (alice, mut tx, spent) = fixture();
+    tx.inputs[1].sighash = SigHashType::ALL.anyone_can_pay();
+    let data = tx.get_data_to_sign(1, &spent[1]).unwrap();
+
+    let mut alone = tx.clone();
+    alone.inputs = vec![tx.inputs[1].clone()];
+    assert_eq!(alone.get_data_to_sign(0, &spent[1]), Some(data));
+
+    let mut last = tx.clone();
+    last.inputs[2].sighash = SigHashType::ALL.anyone_can_pay();
+    assert_ne!(last.get_data_to_sign(2, &spent[2]), alone.get_data_to_sign(0, &spent[1]));
+
+    assert!(tx.sign_input(1, &spent[1], &alice.secret));
+    tx.inputs.swap(1, 2);
+    assert!(is_valid(&tx, 2, &spent[1]));
+}
+
+#[test]
+fn none_with_anyone_can_pay_commits_nothing_else() {
+    let (tx, spent) = signed(SigHashType::NONE.anyone_can_pay(), 2);
+
+    let mut changed = tx.clone();
+    changed.inputs.drain(..2);
+    changed.outputs = vec![extra_output()];
+    assert!(is_valid(&changed, 0, &spent[2]));
+
+    let mut changed = tx.clone();
+    changed.inputs[2].output_index = 1;
+    assert!(!is_valid(&changed, 2, &spent[2]));
+}
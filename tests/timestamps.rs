@@ -0,0 +1,72 @@
+mod common;
+
+use common::{mine, next_block, regtest_chain, remine, TestClock};
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::blockchain::Blockchain;
+use utxo_blockchain::models::errors::BlockValidationError;
+
+/// Corrente com alguns blocos espaçados de 10 minutos e o relógio parado no último
+fn chain_with_clock() -> (Blockchain, TestClock) {
+    let mut bc = regtest_chain();
+    let genesis_time = bc.chain[0].header.timestamp;
+    let clock = TestClock::install(&mut bc, genesis_time);
+    for _ in 0..5 {
+        clock.advance(600);
+        mine(&mut bc, &Wallet::from_seed("miner"));
+    }
+    (bc, clock)
+}
+
+#[test]
+fn median_time_past_is_the_median_of_recent_blocks() {
+    let (bc, _) = chain_with_clock();
+    // Gênesis + 5 blocos a cada 600s: a mediana (superior) é o bloco 3
+    assert_eq!(bc.median_time_past(), bc.chain[3].header.timestamp);
+}
+
+#[test]
+fn timestamp_must_be_after_median_time_past() {
+    let (mut bc, _) = chain_with_clock();
+    let median_time_past = bc.median_time_past();
+    let mut block = next_block(&mut bc, &Wallet::from_seed("miner"));
+
+    block.header.timestamp = median_time_past;
+    assert_eq!(
+        bc.validate_timestamp(&block.header),
+        Err(BlockValidationError::TimestampTooOld { timestamp: median_time_past, median_time_past }),
+    );
+
+    block.header.timestamp = median_time_past + 1;
+    assert_eq!(bc.validate_timestamp(&block.header), Ok(()));
+    remine(&mut block);
+    bc.add_block(block).unwrap();
+}
+
+#[test]
+fn timestamp_may_lead_the_clock_by_max_drift() {
+    let (mut bc, clock) = chain_with_clock();
+    let limit = clock.now() + bc.params.timestamp_rules.max_future_drift;
+    let mut block = next_block(&mut bc, &Wallet::from_seed("miner"));
+
+    block.header.timestamp = limit;
+    assert_eq!(bc.validate_timestamp(&block.header), Ok(()));
+
+    block.header.timestamp = limit + 1;
+    assert_eq!(
+        bc.validate_timestamp(&block.header),
+        Err(BlockValidationError::TimestampTooFarInFuture { timestamp: limit + 1, limit }),
+    );
+    remine(&mut block);
+    assert!(matches!(bc.add_block(block), Err(BlockValidationError::TimestampTooFarInFuture { .. })));
+}
+
+#[test]
+fn future_block_becomes_valid_when_clock_catches_up() {
+    let (mut bc, clock) = chain_with_clock();
+    let mut block = next_block(&mut bc, &Wallet::from_seed("miner"));
+    block.header.timestamp = clock.now() + bc.params.timestamp_rules.max_future_drift + 1;
+    assert!(bc.validate_timestamp(&block.header).is_err());
+
+    clock.advance(1);
+    assert_eq!(bc.validate_timestamp(&block.header), Ok(()));
+}
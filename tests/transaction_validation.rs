@@ -0,0 +1,92 @@
+mod common;
+
+use common::{address, mine, next_block, pay, regtest_chain, remine, spend};
+use utxo_blockchain::crypto::wallet::Wallet;
+use utxo_blockchain::models::errors::{BlockValidationError, ScriptError, TxValidationError};
+use utxo_blockchain::models::script::Op;
+use utxo_blockchain::models::transaction::{Input, Output, Transaction};
+
+#[test]
+fn output_sum_overflow_is_rejected() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    let coinbase = mine(&mut bc, &alice);
+
+    // Somando com wrap daria 1, abaixo dos 50 de entrada
+    let outputs = vec![
+        Output::to_address(u64::MAX, &address(&alice)),
+        Output::to_address(2, &address(&alice)),
+    ];
+    let tx = spend(&alice, &coinbase, 0, outputs);
+
+    assert_eq!(bc.validate_transaction(&tx), Err(TxValidationError::ValueOverflow));
+    assert_eq!(bc.submit_transaction(tx.clone()).unwrap_err(), TxValidationError::ValueOverflow);
+
+    // Também dentro de um bloco
+    let mut block = next_block(&mut bc, &alice);
+    block.transactions.push(tx.clone());
+    remine(&mut block);
+    assert_eq!(
+        bc.add_block(block).unwrap_err(),
+        BlockValidationError::InvalidTransaction { tx_hash: tx.calculate_hash(), error: TxValidationError::ValueOverflow },
+    );
+}
+
+#[test]
+fn outputs_above_inputs_are_rejected() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    let coinbase = mine(&mut bc, &alice);
+
+    let tx = pay(&alice, &coinbase, 0, &alice, 51);
+    assert_eq!(
+        bc.validate_transaction(&tx),
+        Err(TxValidationError::InsufficientFunds { input_value: 50, output_value: 51 }),
+    );
+    assert_eq!(bc.validate_transaction(&pay(&alice, &coinbase, 0, &alice, 50)), Ok(0));
+}
+
+#[test]
+fn inputs_of_different_owners_are_signed_separately() {
+    let (alice, bob, carol) = (Wallet::from_seed("alice"), Wallet::from_seed("bob"), Wallet::from_seed("carol"));
+    let mut bc = regtest_chain();
+    let alice_coins = mine(&mut bc, &alice);
+    let bob_coins = mine(&mut bc, &bob);
+
+    let inputs = vec![Input::new(alice_coins.calculate_hash(), 0), Input::new(bob_coins.calculate_hash(), 0)];
+    let mut tx = Transaction::new(inputs, vec![Output::to_address(99, &address(&carol))]);
+    assert!(tx.sign_input(0, &alice_coins.outputs[0], &alice.secret));
+
+    // A chave de alice não destrava o output de bob
+    let mut forged = tx.clone();
+    assert!(forged.sign_input(1, &bob_coins.outputs[0], &alice.secret));
+    assert!(matches!(bc.validate_transaction(&forged), Err(TxValidationError::InvalidScript { input_index: 1, .. })));
+
+    assert!(tx.sign_input(1, &bob_coins.outputs[0], &bob.secret));
+    assert_eq!(bc.validate_transaction(&tx), Ok(1));
+
+    let mut block = next_block(&mut bc, &alice);
+    block.transactions.push(tx);
+    remine(&mut block);
+    bc.add_block(block).unwrap();
+    assert_eq!(bc.get_balance(&address(&carol)), 99);
+    assert_eq!(bc.get_balance(&address(&bob)), 0);
+}
+
+#[test]
+fn extra_push_in_signed_input_is_rejected() {
+    let alice = Wallet::from_seed("alice");
+    let mut bc = regtest_chain();
+    let coinbase = mine(&mut bc, &alice);
+    let tx = pay(&alice, &coinbase, 0, &alice, 49);
+
+    // O script_sig entra no txid: um push a mais mudaria o hash sem tocar na assinatura
+    let mut malleated = tx.clone();
+    malleated.inputs[0].script_sig.0.insert(0, Op::PushData(b"lixo".to_vec()));
+    assert_ne!(malleated.calculate_hash(), tx.calculate_hash());
+    assert_eq!(
+        bc.validate_transaction(&malleated),
+        Err(TxValidationError::InvalidScript { input_index: 0, error: ScriptError::CleanStack { size: 2 } }),
+    );
+    assert_eq!(bc.validate_transaction(&tx), Ok(1));
+}